@@ -0,0 +1,18 @@
+---- MODULE arity ----
+EXTENDS Naturals, Sequences, FiniteSets
+CONSTANT Op(_, _)
+VARIABLE q
+
+Add(a, b) == a + b
+Inc(a) == a + 1
+Apply(F(_, _), a) == F(a, a)
+
+Init == q = <<>>
+
+Next == \/ q' = Append(q)
+        \/ q' = SubSeq(q, 1, Add(1, 2, 3))
+        \/ q' = <<Cardinality({q}, 1), Op(1, 2)>>
+        \/ q' = <<Apply(Add, 1), Apply(Inc, 1), Apply(LAMBDA a: a, 1)>>
+        \/ q' = <<Apply(LAMBDA a, b: a, 1), Inc>>
+
+====
@@ -0,0 +1,6 @@
+---- MODULE broken_next ----
+VARIABLES x, y
+Init == x = 0 /\ y =
+Next == x' = x
+Spec == Init /\ [][Next]_<<x, y>>
+====
@@ -0,0 +1,9 @@
+---- MODULE broken_params ----
+EXTENDS Naturals
+VARIABLE x
+
+Helper(a == a + 1
+
+Init == x = Start
+Next == x' = x + 1
+====
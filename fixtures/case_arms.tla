@@ -0,0 +1,18 @@
+---- MODULE case_arms ----
+EXTENDS Naturals
+VARIABLE x
+
+Init == x = 0
+
+Label == CASE x = 0 -> "zero"
+           [] x > 0 -> "positive"
+
+Sign == CASE x  =  0 -> 0
+          [] x > 0   -> 1
+          [] x = 0 \* duplicate
+                     -> 2
+          [] OTHER   -> -1
+
+Next == x' = x + Sign /\ Label # ""
+
+====
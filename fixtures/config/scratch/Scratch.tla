@@ -0,0 +1,10 @@
+---- MODULE Scratch ----
+EXTENDS Naturals
+VARIABLES count, unusedVar, lastValue
+
+Init == count = 0 /\ lastValue = 0
+Next == /\ \E count \in 1..2 : lastValue' = count
+        /\ count' = count + 1
+
+Helper == 0
+====
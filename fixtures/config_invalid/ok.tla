@@ -0,0 +1,7 @@
+---- MODULE ok ----
+VARIABLES x
+
+Init == x = 0
+Next == x' = x + 1
+
+====
@@ -0,0 +1,17 @@
+---- MODULE duplicates ----
+EXTENDS Naturals, Sequences
+CONSTANT N
+VARIABLES x, q, x
+VARIABLE N
+
+Init == x = 0 /\ q = <<>> /\ N = 0
+
+Step(a, a) == a
+
+Len(s) == 0
+
+Next == x' = x + 1 /\ UNCHANGED q
+
+Next == x' = Step(1, 2) /\ q' = Append(q, Len(q))
+
+====
@@ -0,0 +1,23 @@
+---- MODULE Fixable ----
+EXTENDS Naturals
+VARIABLES x, unused, y, z
+
+Init == x = 0 /\ y = 0 /\ z = 0
+
+Inc == /\ x' = x + 1
+       /\ y' = y
+
+Swap == /\ x' = y /\ UNCHANGED <<y>>
+
+Reset == /\ x' = 0 /\ UNCHANGED z
+
+Step == CASE x = 0 -> x' = 1 /\ UNCHANGED <<y, z>>
+          [] x > 0 -> x' = 0 /\ UNCHANGED <<y, z>>
+
+Next == \/ Inc
+        \/ Swap
+        \/ Reset
+        \/ Step
+        \/ y' = 1 /\ z' = 2
+
+====
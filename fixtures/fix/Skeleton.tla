@@ -0,0 +1,6 @@
+---- MODULE Skeleton ----
+VARIABLES a, b
+
+TypeOK == a \in {0, 1} /\ b \in {0, 1}
+
+====
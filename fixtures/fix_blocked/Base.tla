@@ -0,0 +1,5 @@
+---- MODULE Base ----
+VARIABLE x
+
+Next == x' = x + 1
+====
@@ -0,0 +1,7 @@
+---- MODULE MC ----
+EXTENDS Base
+
+Start(Init) == x = Init
+
+Spec == Start(0) /\ [][Next]_x
+====
@@ -0,0 +1,7 @@
+---- MODULE Base ----
+EXTENDS Naturals
+
+VARIABLES x, y
+
+TypeOK == x \in Nat
+====
@@ -0,0 +1,9 @@
+---- MODULE MC ----
+EXTENDS Base
+
+Init == x = 0 /\ y = 0
+
+Next == x' = x + 1 /\ y' = y
+
+Inv == y = 0
+====
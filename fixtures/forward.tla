@@ -0,0 +1,11 @@
+---- MODULE forward ----
+EXTENDS Naturals
+VARIABLE x
+
+Init == x = Start
+Start == 0
+Next == x' = LET a == b
+                 b == 1
+             IN x + a
+
+====
@@ -0,0 +1,16 @@
+---- MODULE FunctionLevels ----
+EXTENDS Naturals
+VARIABLE x
+
+g[i \in 1..2] == x' + i
+h[i \in 1..2] == x + i
+
+ASSUME h[1] > 0
+
+Init == /\ x = 0
+        /\ g[1] = 1
+
+Next == x' = x + 1
+
+Inv == g[1] > 0
+====
@@ -0,0 +1,22 @@
+---- MODULE hazards ----
+EXTENDS Naturals, Sequences
+CONSTANT S
+VARIABLES x, q
+
+Big == SUBSET SUBSET S
+Msgs == Seq(S)
+
+Init == /\ x \in Nat
+        /\ q \in [SUBSET S -> S]
+
+Pick == CHOOSE n \in Nat : n > 3
+
+Fact[n \in Nat] == IF n = 0 THEN 1 ELSE n * Fact[n - 1]
+
+TypeOK == x \in Nat /\ q \in [SUBSET S -> S]
+
+Next == /\ \E n \in Nat : x' = x + n + Pick + Fact[2]
+        /\ \E m \in Msgs : Len(m) > 0 /\ q' = q
+        /\ \A y \in 1..3 : y \in Big
+
+====
@@ -0,0 +1,14 @@
+---- MODULE Model ----
+EXTENDS Naturals
+VARIABLE x
+
+Init == x = 0
+Next == x' = (x + 1) % 3
+
+Bounded == \A n \in Nat : n > x => n > 0
+Tidy == \E n \in Nat : x < n
+
+LEMMA Helper == \A n \in Nat : n >= 0
+THEOREM AddZero == \A n \in Nat : n + 0 = n
+  BY \A n \in Nat : n = n
+====
@@ -0,0 +1,7 @@
+---- MODULE if_without_else ----
+VARIABLE x
+
+Init == IF x THEN 1
+
+Next == x' = x
+====
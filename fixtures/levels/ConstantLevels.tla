@@ -0,0 +1,12 @@
+---- MODULE ConstantLevels ----
+EXTENDS Naturals
+CONSTANTS N, Leq(_, _)
+VARIABLE x
+
+ASSUME x > N
+ASSUME Positive == N > 0 /\ Leq(N, 1)
+
+Init == x = 0
+Next == x' = x + 1 /\ Leq(x, N)
+
+====
@@ -0,0 +1,14 @@
+---- MODULE Levels ----
+EXTENDS Naturals
+VARIABLES x, y
+vars == <<x, y>>
+TypeOK == x \in Nat /\ y \in Nat
+Inc == x' = x + 1 /\ y' = y
+Safe == x > 0 \/ UNCHANGED x
+Live == <>(x > 3)
+Init == x = 0 /\ y = 0 /\ Live
+Next == \/ Inc /\ ENABLED Inc
+        \/ [](x > 0) /\ UNCHANGED vars
+        \/ UNCHANGED Inc
+Spec == Init /\ [][Next]_vars /\ WF_vars(Next) /\ [][Inc]_(x')
+====
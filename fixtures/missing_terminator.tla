@@ -0,0 +1,6 @@
+---- MODULE missing_terminator ----
+VARIABLE x
+
+Init == x = 0
+
+Next == x' = x + 1
@@ -0,0 +1,21 @@
+---- MODULE Right ----
+VARIABLE x
+
+---- MODULE Inner ----
+a == 1
+====
+
+---- MODULE Inner ----
+b == 2
+====
+
+---- MODULE Right ----
+c == 3
+====
+
+Init == x = 0
+Next == x' = x
+====
+
+---- MODULE Extra ----
+====
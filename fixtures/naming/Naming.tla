@@ -0,0 +1,20 @@
+---- MODULE Naming ----
+EXTENDS Naturals
+CONSTANTS MAX_PROCS, timeout
+VARIABLES pcState, Msg_Queue
+
+vars == <<pcState, Msg_Queue>>
+
+TypeOK == pcState \in Nat /\ Msg_Queue \in Nat
+
+Safe == pcState <= MAX_PROCS + timeout
+
+Init == pcState = 0 /\ Msg_Queue = 0
+
+send_msg == pcState' = pcState + 1 /\ UNCHANGED Msg_Queue
+
+Next == send_msg
+
+Spec == Init /\ [][Next]_vars
+
+====
@@ -0,0 +1,40 @@
+---- MODULE Edited ----
+EXTENDS Naturals
+
+(* --algorithm Edited
+variables x = 0;
+
+begin
+Inc:
+  while x < 3 do
+    x := x + 2;
+  end while;
+end algorithm; *)
+\* BEGIN TRANSLATION (chksum(pcal) = "5b92e0a4" /\ chksum(tla) = "c3d87f15")
+VARIABLES x, pc
+
+vars == << x, pc >>
+
+Init == (* Global variables *)
+        /\ x = 0
+        /\ pc = "Inc"
+
+Inc == /\ pc = "Inc"
+       /\ IF x < 3
+             THEN /\ x' = x + 2
+                  /\ pc' = "Inc"
+             ELSE /\ pc' = "Done"
+                  /\ x' = x
+
+(* Allow infinite stuttering to prevent deadlock on termination. *)
+Terminating == pc = "Done" /\ UNCHANGED vars
+
+Next == Inc
+           \/ Terminating
+
+Spec == Init /\ [][Next]_vars
+
+Termination == <>(pc = "Done")
+
+\* END TRANSLATION 
+====
@@ -0,0 +1,4 @@
+#!/bin/sh
+# Stands in for `java -cp tla2tools.jar pcal.trans`: replaces the module
+# named by the last argument with the translation given as the first.
+cp "$1" "$2"
@@ -0,0 +1,11 @@
+---- MODULE Untranslated ----
+EXTENDS Naturals
+
+(* --algorithm Untranslated {
+  variables x = 0;
+  {
+    L: while (x < 3) { x := x + 1 };
+  }
+} *)
+
+====
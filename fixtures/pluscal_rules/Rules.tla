@@ -0,0 +1,49 @@
+---- MODULE Rules ----
+EXTENDS Naturals
+
+(* --algorithm Rules
+variables x = 0, y = 0;
+
+macro Bump() begin
+  x := x + 1;
+end macro;
+
+procedure Reset()
+begin
+R1:
+  x := 0;
+  return;
+  y := 0;
+end procedure;
+
+process Worker \in 1..2
+variables done = FALSE;
+begin
+W1:
+  x := 1;
+  Bump();
+  while y < 3 do
+    y := y + 1;
+  end while;
+W2:
+  call Reset();
+  done := TRUE;
+W3:
+  y := 2;
+  await x > 0;
+  z := 1;
+  y := 3 || done := FALSE;
+end process;
+
+process Single = 3
+begin
+  skip;
+S1:
+  if x > 0 then
+    goto S1;
+  end if;
+  x := 5;
+end process;
+end algorithm; *)
+
+====
@@ -0,0 +1,26 @@
+---- MODULE Primes ----
+EXTENDS Naturals
+CONSTANT N
+VARIABLES x, y
+
+vars == <<x, y>>
+
+Reset == x' = 0
+
+Init == /\ y = 0
+        /\ Reset
+
+TypeOK == x \in Nat /\ y' \in Nat
+
+CanStep == ENABLED (x' = x + 1)
+
+Inc == /\ x' = x + N'
+       /\ \E n \in 1..N : y' = n'
+
+Twice == x'' = x /\ UNCHANGED y
+
+Next == Inc \/ Twice
+
+Spec == Init /\ [][Next]_vars
+
+====
@@ -0,0 +1,9 @@
+---- MODULE recursive ----
+EXTENDS Naturals
+RECURSIVE Sum(_), Ghost(_), Odd(_)
+Sum(s, n) == IF n = 0 THEN 0 ELSE Sum(s, n - 1)
+Fact(n) == IF n = 0 THEN 1 ELSE n * Fact(n - 1)
+Even(n) == IF n = 0 THEN TRUE ELSE Odd(n - 1)
+Odd(n) == IF n = 0 THEN FALSE ELSE Even(n - 1)
+f[n \in Nat] == IF n = 0 THEN 0 ELSE f[n - 1]
+====
@@ -0,0 +1,8 @@
+---- MODULE shadowed ----
+EXTENDS Naturals
+VARIABLES x, y
+
+Init == y = 0
+Next == y' = CHOOSE x \in 1..3 : x > y
+
+====
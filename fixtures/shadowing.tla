@@ -0,0 +1,19 @@
+---- MODULE shadowing ----
+EXTENDS Naturals
+CONSTANT N
+VARIABLE x
+
+Max(S) == CHOOSE m \in S : \A n \in S : n <= m
+
+Init == x = 0
+
+Step(N) == x' = x + N
+
+Bump == LET Max == 3 IN x' = Max
+
+Next == \/ \E x \in 1..3 : Step(x)
+        \/ x' = {Max({x}) : x \in 1..N}
+        \/ x' = [Init \in 1..N |-> Init][1]
+        \/ Bump
+
+====
@@ -0,0 +1,17 @@
+---- MODULE SpecShape ----
+EXTENDS Naturals
+VARIABLES x, y, z
+
+vars == <<x, y>>
+
+Init == x = 0 /\ y = 0 /\ z = 0
+
+Next == x' = x + 1 /\ y' = y /\ z' = z
+
+Fairness == WF_<<x, y, z>>(Next)
+
+Spec == Init /\ [][Next]_vars /\ Fairness
+
+LiveSpec == x = 0 /\ [][Next]_<<x, y, z>> /\ SF_<<x, y, z>>(Next)
+
+====
@@ -0,0 +1,21 @@
+---- MODULE suppress ----
+\* tla-lint: disable-file TLA002
+
+\* tla-lint: disable-next-line TLA005
+CONSTANTS Template
+
+(* tla-lint: disable TLA005 *)
+CONSTANTS Slot1, Slot2
+(* tla-lint: enable TLA005 *)
+
+CONSTANTS Loud
+
+\* tla-lint: disable-next-line TLA001, TLA004
+VARIABLE spare
+
+(* tla-lint: disable *)
+(* tla-lint: enable TLA004 *)
+\* tla-lint: silence TLA001
+\* tla-lint: disable-next-line TLA999
+
+====
@@ -0,0 +1,15 @@
+---- MODULE suppress_broken ----
+VARIABLE x
+
+\* tla-lint: disable-next-line TLA004
+Helper == x + 1
+
+(* tla-lint: disable *)
+Other == x - 1
+(* tla-lint: enable *)
+
+Init == x = (0
+
+\* tla-lint: disable-next-line TLA013
+Next == x' = x
+====
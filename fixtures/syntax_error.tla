@@ -0,0 +1,7 @@
+---- MODULE syntax_error ----
+VARIABLES x, y
+
+Init == x = 0 /\ y = 0
+Next == x' = (x + 1 /\ y' = y
+
+====
@@ -0,0 +1,27 @@
+---- MODULE unchanged ----
+EXTENDS Naturals
+VARIABLES x, y, z
+
+vars == <<x, y, z>>
+
+Init == x = 0 /\ y = 0 /\ z = 0
+
+Inc == /\ x' = x + 1
+       /\ UNCHANGED <<y, z>>
+
+Reset == /\ x' = 0
+         /\ y' = 0
+
+Swap == IF x > y
+          THEN /\ x' = y /\ y' = x /\ UNCHANGED z
+          ELSE /\ z' = z + 1 /\ UNCHANGED x
+
+Idle == UNCHANGED vars
+
+Next == \/ Inc
+        \/ Reset
+        \/ \E n \in 1..3 : Swap
+        \/ Idle
+        \/ x' = 1
+
+====
@@ -0,0 +1,9 @@
+---- MODULE unclosed_paren ----
+EXTENDS Naturals
+VARIABLE x
+
+Inv == \A i \in 1..3 : (i > 0 /\ x > 0
+Init == x = 0
+Next == x' = x
+
+====
@@ -0,0 +1,10 @@
+---- MODULE undefined ----
+EXTENDS Naturals, Sequences
+VARIABLES queue
+
+Init == queue = <<>>
+Enq(v) == queue' = Append(queue, v)
+Next == \E v \in 1..3 : Enq(v) \/ Deq
+Inv == \A i \in 1..Len(queue) : queue[i] \in 1..Max
+
+====
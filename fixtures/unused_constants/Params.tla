@@ -0,0 +1,11 @@
+---- MODULE Params ----
+EXTENDS Naturals
+CONSTANTS Limit, Workers, Seed, Unused
+VARIABLES n
+
+ASSUME Workers > 0
+
+Init == n = 0
+Next == n' = (n + 1) % Limit
+
+====
@@ -0,0 +1,5 @@
+---- MODULE Extra ----
+
+Zero == 0
+
+====
@@ -0,0 +1,7 @@
+---- MODULE Helpers ----
+EXTENDS Naturals
+
+a ++ b == a + b + 1
+Double(n) == 2 * n
+
+====
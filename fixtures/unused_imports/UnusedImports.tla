@@ -0,0 +1,12 @@
+---- MODULE UnusedImports ----
+EXTENDS Naturals, Sequences, FiniteSets, TLC, Helpers, Extra
+VARIABLES x, log
+
+H == INSTANCE Helpers
+E == INSTANCE Extra
+
+Init == x = 0 /\ log = <<>>
+Next == /\ x' = (x ++ 1) + H!Double(x)
+        /\ log' = log \o <<x>>
+
+====
@@ -0,0 +1,17 @@
+---- MODULE Counter ----
+EXTENDS Naturals
+CONSTANT Limit
+VARIABLES count
+
+TypeOK == count \in 0..Limit
+Step(c) == IF c < Limit THEN c + 1 ELSE 0
+Dead == count * 2
+RECURSIVE Countdown(_)
+Countdown(n) == IF n = 0 THEN 0 ELSE Countdown(n - 1)
+CheckedByMC == count >= 0
+
+Init == count = 0
+Next == count' = Step(count)
+Spec == Init /\ [][Next]_count
+
+====
@@ -0,0 +1,6 @@
+---- MODULE MC ----
+EXTENDS Counter
+
+MCInv == CheckedByMC
+
+====
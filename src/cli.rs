@@ -20,6 +20,18 @@ pub enum Command {
         /// Output JSON (for editors/CI)
         #[arg(long)]
         json: bool,
+
+        /// Apply the available fixes, then report what remains
+        #[arg(long)]
+        fix: bool,
+
+        /// List every rule with its category and default severity
+        #[arg(long, conflicts_with_all = ["fix", "explain"])]
+        list_rules: bool,
+
+        /// Describe a rule in detail, e.g. `--explain TLA002`
+        #[arg(long, value_name = "CODE", conflicts_with_all = ["fix", "json"])]
+        explain: Option<String>,
     },
 
     /// Format TLA+ files
@@ -3,5 +3,6 @@ pub mod cli;
 pub mod doctor;
 pub mod fmt;
 pub mod lint;
+pub mod semantic;
 pub mod tla_parser;
 pub mod tooling;
@@ -0,0 +1,475 @@
+use crate::semantic::{SymbolId, SymbolKind, SymbolTable, node_for_range};
+use std::collections::HashSet;
+use tree_sitter::{Node, Tree};
+
+/// One alternative of the next-state relation.
+#[derive(Clone, Copy, Debug)]
+pub struct Action<'t> {
+    /// The expression describing the step.
+    pub node: Node<'t>,
+    /// The innermost operator the step was reached through, if any.
+    pub operator: Option<SymbolId>,
+}
+
+/// The module-level operator definition named `name`, if the file has one.
+pub fn find_operator<'t>(
+    tree: &'t Tree,
+    symbols: &SymbolTable,
+    name: &str,
+) -> Option<(SymbolId, Node<'t>)> {
+    let (id, sym) = symbols
+        .module_symbols()
+        .find(|(_, s)| s.kind == SymbolKind::Operator && s.name == name)?;
+    let node = node_for_range(
+        tree.root_node(),
+        &sym.definition_range,
+        "operator_definition",
+    )?;
+    Some((id, node))
+}
+
+/// Splits the body of `Next` into its actions, following disjunctions,
+/// existential quantifiers, IF/CASE branches and calls to operators
+/// defined in the same file.
+pub fn next_actions<'t>(tree: &'t Tree, symbols: &SymbolTable) -> Vec<Action<'t>> {
+    let mut actions = Vec::new();
+    if let Some((id, next)) = find_operator(tree, symbols, "Next") {
+        if let Some(body) = next.child_by_field_name("definition") {
+            let mut visiting = vec![id];
+            split_disjuncts(tree, symbols, body, None, &mut visiting, &mut actions);
+        }
+    }
+    actions
+}
+
+fn split_disjuncts<'t>(
+    tree: &'t Tree,
+    symbols: &SymbolTable,
+    node: Node<'t>,
+    operator: Option<SymbolId>,
+    visiting: &mut Vec<SymbolId>,
+    out: &mut Vec<Action<'t>>,
+) {
+    let recurse = |child: Node<'t>, visiting: &mut Vec<SymbolId>, out: &mut Vec<Action<'t>>| {
+        split_disjuncts(tree, symbols, child, operator, visiting, out)
+    };
+
+    match node.kind() {
+        "disj_list" => {
+            let mut cursor = node.walk();
+            for item in node.named_children(&mut cursor) {
+                if let Some(expr) = item.named_child(1) {
+                    recurse(expr, visiting, out);
+                }
+            }
+        }
+        "bound_infix_op" if op_symbol(node) == Some("lor") => {
+            for field in ["lhs", "rhs"] {
+                if let Some(side) = node.child_by_field_name(field) {
+                    recurse(side, visiting, out);
+                }
+            }
+        }
+        "parentheses" => {
+            if let Some(inner) = node.named_child(0) {
+                recurse(inner, visiting, out);
+            }
+        }
+        "bounded_quantification" if quantifier(node) == Some("exists") => {
+            if let Some(body) = node.child_by_field_name("expression") {
+                recurse(body, visiting, out);
+            }
+        }
+        "let_in" => {
+            if let Some(body) = node.child_by_field_name("expression") {
+                recurse(body, visiting, out);
+            }
+        }
+        "if_then_else" => {
+            for field in ["then", "else"] {
+                if let Some(branch) = node.child_by_field_name(field) {
+                    recurse(branch, visiting, out);
+                }
+            }
+        }
+        "case" => {
+            for arm in case_arm_bodies(node) {
+                recurse(arm, visiting, out);
+            }
+        }
+        "identifier_ref" | "bound_op" => match called_operator(tree, symbols, node) {
+            Some((id, body)) if !visiting.contains(&id) => {
+                visiting.push(id);
+                split_disjuncts(tree, symbols, body, Some(id), visiting, out);
+                visiting.pop();
+            }
+            _ => out.push(Action { node, operator }),
+        },
+        _ => out.push(Action { node, operator }),
+    }
+}
+
+/// Variables an action assigns with a prime or leaves UNCHANGED. Branches of
+/// a disjunction, IF or CASE only count variables every branch covers.
+/// `None` means the action calls something we cannot see into, such as an
+/// operator of another module, and may cover any variable.
+pub fn covered_variables(
+    tree: &Tree,
+    symbols: &SymbolTable,
+    node: Node,
+) -> Option<HashSet<SymbolId>> {
+    let mut visiting = Vec::new();
+    covered(tree, symbols, node, &mut visiting)
+}
+
+fn covered(
+    tree: &Tree,
+    symbols: &SymbolTable,
+    node: Node,
+    visiting: &mut Vec<SymbolId>,
+) -> Option<HashSet<SymbolId>> {
+    let each = |nodes: Vec<Node>, visiting: &mut Vec<SymbolId>| {
+        nodes
+            .into_iter()
+            .map(|n| covered(tree, symbols, n, visiting))
+            .collect::<Vec<_>>()
+    };
+
+    match node.kind() {
+        "conj_list" | "disj_list" => {
+            let mut cursor = node.walk();
+            let items: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter_map(|item| item.named_child(1))
+                .collect();
+            let sets = each(items, visiting);
+            if node.kind() == "conj_list" {
+                union(sets)
+            } else {
+                intersection(sets)
+            }
+        }
+        "bound_infix_op" if matches!(op_symbol(node), Some("land" | "lor")) => {
+            let sides: Vec<Node> = ["lhs", "rhs"]
+                .iter()
+                .filter_map(|f| node.child_by_field_name(f))
+                .collect();
+            let sets = each(sides, visiting);
+            if op_symbol(node) == Some("land") {
+                union(sets)
+            } else {
+                intersection(sets)
+            }
+        }
+        "if_then_else" => {
+            let branches: Vec<Node> = ["then", "else"]
+                .iter()
+                .filter_map(|f| node.child_by_field_name(f))
+                .collect();
+            intersection(each(branches, visiting))
+        }
+        "case" => intersection(each(case_arm_bodies(node), visiting)),
+        "parentheses" => match node.named_child(0) {
+            Some(inner) => covered(tree, symbols, inner, visiting),
+            None => Some(HashSet::new()),
+        },
+        "bounded_quantification" | "unbounded_quantification" | "let_in" => {
+            match node.child_by_field_name("expression") {
+                Some(body) => covered(tree, symbols, body, visiting),
+                None => Some(HashSet::new()),
+            }
+        }
+        "prefixed_op" => None,
+        "identifier_ref" | "bound_op" => {
+            if let Some((id, body)) = called_operator(tree, symbols, node) {
+                if visiting.contains(&id) {
+                    return Some(HashSet::new());
+                }
+                visiting.push(id);
+                let result = covered(tree, symbols, body, visiting);
+                visiting.pop();
+                return result;
+            }
+            let name = if node.kind() == "bound_op" {
+                node.child_by_field_name("name")
+            } else {
+                Some(node)
+            };
+            match name
+                .and_then(|n| symbols.resolve(n))
+                .map(|id| symbols.symbol(id).kind)
+            {
+                Some(SymbolKind::Imported | SymbolKind::ModuleInstance) | None => None,
+                Some(_) => Some(primed_in(tree, symbols, node, visiting)),
+            }
+        }
+        _ => Some(primed_in(tree, symbols, node, visiting)),
+    }
+}
+
+/// Variables primed or left UNCHANGED in any branch of `node`, following
+/// calls to operators of this file.
+pub fn touched_variables(tree: &Tree, symbols: &SymbolTable, node: Node) -> HashSet<SymbolId> {
+    let mut out = HashSet::new();
+    let mut visiting = Vec::new();
+    touched(tree, symbols, node, &mut visiting, &mut out);
+    out
+}
+
+fn touched(
+    tree: &Tree,
+    symbols: &SymbolTable,
+    node: Node,
+    visiting: &mut Vec<SymbolId>,
+    out: &mut HashSet<SymbolId>,
+) {
+    let is_unchanged = node.kind() == "bound_prefix_op" && op_symbol(node) == Some("unchanged");
+    if is_prime(node) || is_unchanged {
+        out.extend(primed_in(tree, symbols, node, visiting));
+        return;
+    }
+    // The name of a `bound_op` is an `identifier_ref` child.
+    if node.kind() == "identifier_ref" {
+        if let Some((id, body)) = called_operator(tree, symbols, node) {
+            if !visiting.contains(&id) {
+                visiting.push(id);
+                touched(tree, symbols, body, visiting, out);
+                visiting.pop();
+            }
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        touched(tree, symbols, child, visiting, out);
+    }
+}
+
+/// Variables primed (`x'`, `<<x, y>>'`, `vars'`) or listed in UNCHANGED
+/// anywhere inside `node`.
+fn primed_in(
+    tree: &Tree,
+    symbols: &SymbolTable,
+    node: Node,
+    visiting: &mut Vec<SymbolId>,
+) -> HashSet<SymbolId> {
+    let mut out = HashSet::new();
+    let is_unchanged = node.kind() == "bound_prefix_op" && op_symbol(node) == Some("unchanged");
+    let operand = if is_prime(node) {
+        node.child_by_field_name("lhs")
+    } else if is_unchanged {
+        node.child_by_field_name("rhs")
+    } else {
+        None
+    };
+    if let Some(operand) = operand {
+        out.extend(state_tuple(tree, symbols, operand, visiting));
+        return out;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        out.extend(primed_in(tree, symbols, child, visiting));
+    }
+    out
+}
+
+/// The variables named by a UNCHANGED/prime operand, expanding tuples and
+/// zero-argument operators such as `vars == <<x, y>>`.
+pub fn state_tuple(
+    tree: &Tree,
+    symbols: &SymbolTable,
+    node: Node,
+    visiting: &mut Vec<SymbolId>,
+) -> HashSet<SymbolId> {
+    let mut out = HashSet::new();
+    match node.kind() {
+        "identifier_ref" => match symbols.resolve(node) {
+            Some(id) if symbols.symbol(id).kind == SymbolKind::Variable => {
+                out.insert(id);
+            }
+            Some(_) => {
+                if let Some((id, body)) = called_operator(tree, symbols, node) {
+                    if !visiting.contains(&id) {
+                        visiting.push(id);
+                        out.extend(state_tuple(tree, symbols, body, visiting));
+                        visiting.pop();
+                    }
+                }
+            }
+            None => {}
+        },
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                out.extend(state_tuple(tree, symbols, child, visiting));
+            }
+        }
+    }
+    out
+}
+
+/// Outermost primed expressions in the definition of `root` or any operator
+/// of this file it calls. `ENABLED` bodies are skipped since priming is legal
+/// there even inside state predicates.
+pub fn primes_reachable_from<'t>(
+    tree: &'t Tree,
+    symbols: &SymbolTable,
+    root: SymbolId,
+) -> Vec<Node<'t>> {
+    reachable_matching(tree, symbols, root, is_prime, |node| {
+        node.kind() == "bound_prefix_op" && op_symbol(node) == Some("enabled")
+    })
+}
+
+/// Outermost nodes satisfying `matches` in the definition of `root` or any
+/// operator of this file it calls, not looking inside nodes that `skip`.
+pub fn reachable_matching<'t>(
+    tree: &'t Tree,
+    symbols: &SymbolTable,
+    root: SymbolId,
+    matches: impl Fn(Node) -> bool,
+    skip: impl Fn(Node) -> bool,
+) -> Vec<Node<'t>> {
+    let mut found = Vec::new();
+    let mut visited = vec![root];
+    let sym = symbols.symbol(root);
+    let Some(def) = node_for_range(
+        tree.root_node(),
+        &sym.definition_range,
+        "operator_definition",
+    ) else {
+        return found;
+    };
+    let mut stack: Vec<Node<'t>> = def.child_by_field_name("definition").into_iter().collect();
+    while let Some(node) = stack.pop() {
+        if matches(node) {
+            found.push(node);
+            continue;
+        }
+        if skip(node) {
+            continue;
+        }
+        if node.kind() == "identifier_ref" {
+            if let Some((id, body)) = called_operator(tree, symbols, node) {
+                if !visited.contains(&id) {
+                    visited.push(id);
+                    stack.push(body);
+                }
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    found.sort_by_key(|n| n.start_byte());
+    found
+}
+
+/// Bodies of the definitions of `roots` and of every operator of this file
+/// they call, leaving out bodies nested in another one.
+pub fn reachable_definitions<'t>(
+    tree: &'t Tree,
+    symbols: &SymbolTable,
+    roots: impl IntoIterator<Item = SymbolId>,
+) -> Vec<Node<'t>> {
+    let mut visited = Vec::new();
+    let mut bodies = Vec::new();
+    let mut stack = Vec::new();
+    for root in roots {
+        if visited.contains(&root) {
+            continue;
+        }
+        visited.push(root);
+        let sym = symbols.symbol(root);
+        if let Some(body) = node_for_range(
+            tree.root_node(),
+            &sym.definition_range,
+            "operator_definition",
+        )
+        .and_then(|def| def.child_by_field_name("definition"))
+        {
+            bodies.push(body);
+            stack.push(body);
+        }
+    }
+    while let Some(node) = stack.pop() {
+        if node.kind() == "identifier_ref" {
+            if let Some((id, body)) = called_operator(tree, symbols, node) {
+                if !visited.contains(&id) {
+                    visited.push(id);
+                    bodies.push(body);
+                    stack.push(body);
+                }
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    let nested = |body: &Node| {
+        bodies
+            .iter()
+            .any(|outer| outer != body && outer.byte_range().contains(&body.start_byte()))
+    };
+    let mut outermost: Vec<Node> = bodies.iter().copied().filter(|b| !nested(b)).collect();
+    outermost.sort_by_key(|n| n.start_byte());
+    outermost
+}
+
+pub fn is_prime(node: Node) -> bool {
+    node.kind() == "bound_postfix_op" && op_symbol(node) == Some("prime")
+}
+
+/// For `Op` or `Op(args)` naming an operator defined in this file, or `f`
+/// naming a function defined in it, returns its symbol and definition body.
+fn called_operator<'t>(
+    tree: &'t Tree,
+    symbols: &SymbolTable,
+    node: Node,
+) -> Option<(SymbolId, Node<'t>)> {
+    let name = if node.kind() == "bound_op" {
+        node.child_by_field_name("name")?
+    } else {
+        node
+    };
+    let id = symbols.resolve(name)?;
+    let sym = symbols.symbol(id);
+    let kind = match sym.kind {
+        SymbolKind::Operator => "operator_definition",
+        SymbolKind::Function => "function_definition",
+        _ => return None,
+    };
+    let def = node_for_range(tree.root_node(), &sym.definition_range, kind)?;
+    Some((id, def.child_by_field_name("definition")?))
+}
+
+/// The symbol of an infix, prefix or postfix operator application.
+pub fn op_symbol(node: Node) -> Option<&'static str> {
+    node.child_by_field_name("symbol").map(|s| s.kind())
+}
+
+fn quantifier(node: Node) -> Option<&'static str> {
+    node.child_by_field_name("quantifier").map(|s| s.kind())
+}
+
+fn case_arm_bodies(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|c| matches!(c.kind(), "case_arm" | "other_arm"))
+        .filter_map(|arm| arm.named_child(arm.named_child_count().checked_sub(1)?))
+        .collect()
+}
+
+fn union(sets: Vec<Option<HashSet<SymbolId>>>) -> Option<HashSet<SymbolId>> {
+    let mut out = HashSet::new();
+    for set in sets {
+        out.extend(set?);
+    }
+    Some(out)
+}
+
+/// Unknown branches (`None`) do not narrow the result.
+fn intersection(sets: Vec<Option<HashSet<SymbolId>>>) -> Option<HashSet<SymbolId>> {
+    sets.into_iter()
+        .flatten()
+        .reduce(|acc, set| acc.intersection(&set).copied().collect())
+}
@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+
+/// Names a TLC model configuration refers to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlcConfig {
+    /// Operators named by INIT, NEXT, SPECIFICATION, INVARIANT, PROPERTY,
+    /// CONSTRAINT, ACTION_CONSTRAINT, SYMMETRY, VIEW, ALIAS, POSTCONDITION,
+    /// and the right-hand side of `<-` substitutions.
+    pub operators: HashSet<String>,
+    /// CONSTANTS given a value (`N = 3`) or substituted (`N <- Op`).
+    pub constants: HashSet<String>,
+    /// Operators checked as state invariants (INVARIANT/INVARIANTS).
+    pub invariants: HashSet<String>,
+    /// Temporal formulas named by SPECIFICATION.
+    pub specifications: HashSet<String>,
+}
+
+/// Parses the subset of the TLC `.cfg` syntax needed to know which names it
+/// references. Unknown sections and malformed entries are skipped.
+pub fn parse(src: &str) -> TlcConfig {
+    let tokens = tokenize(src);
+    let mut config = TlcConfig::default();
+    let mut in_constants = false;
+    let mut in_invariants = false;
+    let mut in_specification = false;
+    let mut idx = 0;
+
+    while idx < tokens.len() {
+        let tok = tokens[idx];
+        if is_section_keyword(tok) {
+            in_constants = matches!(tok, "CONSTANT" | "CONSTANTS");
+            in_invariants = matches!(tok, "INVARIANT" | "INVARIANTS");
+            in_specification = tok == "SPECIFICATION";
+            idx += 1;
+            continue;
+        }
+
+        if in_constants {
+            match tokens.get(idx + 1).copied() {
+                Some("=") if is_identifier(tok) => {
+                    config.constants.insert(tok.to_string());
+                    idx = skip_value(&tokens, idx + 2);
+                    continue;
+                }
+                Some("<-") if is_identifier(tok) => {
+                    config.constants.insert(tok.to_string());
+                    let mut rhs = idx + 2;
+                    // `N <- [Mod] Op` substitutes an operator of another module.
+                    if tokens.get(rhs) == Some(&"[") {
+                        while rhs < tokens.len() && tokens[rhs] != "]" {
+                            rhs += 1;
+                        }
+                        rhs += 1;
+                    }
+                    if let Some(op) = tokens.get(rhs).filter(|t| is_identifier(t)) {
+                        config.operators.insert(op.to_string());
+                    }
+                    idx = rhs + 1;
+                    continue;
+                }
+                _ => {}
+            }
+        } else if is_identifier(tok) && !matches!(tok, "TRUE" | "FALSE") {
+            config.operators.insert(tok.to_string());
+            if in_invariants {
+                config.invariants.insert(tok.to_string());
+            }
+            if in_specification {
+                config.specifications.insert(tok.to_string());
+            }
+        }
+        idx += 1;
+    }
+
+    config
+}
+
+fn is_section_keyword(tok: &str) -> bool {
+    matches!(
+        tok,
+        "CONSTANT"
+            | "CONSTANTS"
+            | "INIT"
+            | "NEXT"
+            | "SPECIFICATION"
+            | "INVARIANT"
+            | "INVARIANTS"
+            | "PROPERTY"
+            | "PROPERTIES"
+            | "CONSTRAINT"
+            | "CONSTRAINTS"
+            | "ACTION_CONSTRAINT"
+            | "ACTION_CONSTRAINTS"
+            | "SYMMETRY"
+            | "VIEW"
+            | "ALIAS"
+            | "POSTCONDITION"
+            | "CHECK_DEADLOCK"
+    )
+}
+
+fn is_identifier(tok: &str) -> bool {
+    tok.chars().any(|c| c.is_ascii_alphabetic())
+        && tok.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Skips a constant value: a single token or a balanced `{ ... }` set.
+fn skip_value(tokens: &[&str], mut idx: usize) -> usize {
+    let mut depth = 0usize;
+    while idx < tokens.len() {
+        match tokens[idx] {
+            "{" => depth += 1,
+            "}" => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        idx += 1;
+        if depth == 0 {
+            break;
+        }
+    }
+    idx
+}
+
+fn tokenize(src: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let bytes = src.as_bytes();
+    let mut idx = 0;
+    let mut comment_depth = 0usize;
+
+    while idx < bytes.len() {
+        let rest = &src[idx..];
+        if rest.starts_with("(*") {
+            comment_depth += 1;
+            idx += 2;
+        } else if comment_depth > 0 {
+            if rest.starts_with("*)") {
+                comment_depth -= 1;
+                idx += 2;
+            } else {
+                idx += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        } else if rest.starts_with("\\*") {
+            idx += rest.find('\n').unwrap_or(rest.len());
+        } else if rest.starts_with("<-") {
+            tokens.push("<-");
+            idx += 2;
+        } else if bytes[idx].is_ascii_alphanumeric() || bytes[idx] == b'_' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(&rest[..len]);
+            idx += len;
+        } else if bytes[idx] == b'"' {
+            let len = rest[1..].find('"').map_or(rest.len(), |end| end + 2);
+            tokens.push(&rest[..len]);
+            idx += len;
+        } else {
+            let len = rest.chars().next().map_or(1, char::len_utf8);
+            if !rest[..len].trim().is_empty() {
+                tokens.push(&rest[..len]);
+            }
+            idx += len;
+        }
+    }
+    tokens
+}
@@ -0,0 +1,207 @@
+use crate::lint::LintOptions;
+use crate::lint::naming::{Case, NamingConventions};
+use crate::lint::registry::{self, Category};
+use crate::lint::types::{Diagnostic, RuleCode, Severity};
+use crate::tooling::PcalTranslator;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde::de::{self, Deserializer};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Name of the project configuration file.
+pub const FILE_NAME: &str = "tla.toml";
+
+/// The opt-in naming-convention rules.
+fn naming_rules() -> impl Iterator<Item = RuleCode> {
+    registry::RULES
+        .iter()
+        .filter(|rule| rule.category == Category::Naming)
+        .map(|rule| rule.code)
+}
+
+/// Which rules report and how loudly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RuleSettings {
+    /// Rules that are off by default and turned on.
+    pub enabled: HashSet<RuleCode>,
+    pub disabled: HashSet<RuleCode>,
+    pub severities: HashMap<RuleCode, Severity>,
+}
+
+impl RuleSettings {
+    /// Whether `code` reports: it is on by default or enabled, and not
+    /// disabled.
+    pub fn is_enabled(&self, code: RuleCode) -> bool {
+        !self.disabled.contains(&code)
+            && (registry::rule(code).enabled_by_default || self.enabled.contains(&code))
+    }
+
+    /// Drops diagnostics of rules that are off and applies severity
+    /// overrides.
+    pub fn apply(&self, diags: &mut Vec<Diagnostic>) {
+        diags.retain(|d| self.is_enabled(d.code));
+        for d in diags {
+            if let Some(severity) = self.severities.get(&d.code) {
+                d.severity = *severity;
+            }
+        }
+    }
+}
+
+/// Finds the `tla.toml` for each linted file, walking up from its
+/// directory, and caches the parsed result per directory.
+#[derive(Default)]
+pub struct Discovery {
+    cache: HashMap<PathBuf, Rc<LintOptions>>,
+}
+
+impl Discovery {
+    pub fn options_for(&mut self, file: &Path) -> Result<Rc<LintOptions>> {
+        let dir = match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        if let Some(found) = self.cache.get(&dir) {
+            return Ok(Rc::clone(found));
+        }
+        let options = match find(&dir) {
+            Some(path) => {
+                let src = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                parse(&src).with_context(|| format!("invalid {}", path.display()))?
+            }
+            None => LintOptions::default(),
+        };
+        let options = Rc::new(LintOptions {
+            translator: PcalTranslator::from_env(),
+            ..options
+        });
+        self.cache.insert(dir, Rc::clone(&options));
+        Ok(options)
+    }
+}
+
+/// The nearest `tla.toml` in `dir` or one of its ancestors.
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// Parses a `tla.toml`:
+///
+/// ```toml
+/// [lint]
+/// disable = ["TLA004"]
+/// enable = ["TLA015"]      # opt-in rules
+///
+/// [lint.severity]
+/// TLA014 = "error"
+///
+/// [lint.naming]            # options of TLA015-TLA018
+/// operators = "PascalCase"
+/// invariant_prefixes = ["TypeOK", "Inv"]
+/// ```
+///
+/// Unknown sections, keys and rule codes are errors so that typos do not
+/// silently change nothing.
+pub fn parse(src: &str) -> Result<LintOptions> {
+    let file: File = toml::from_str(src)?;
+    let lint = file.lint.unwrap_or_default();
+    let mut enabled: HashSet<RuleCode> = lint.enable.into_iter().map(|c| c.0).collect();
+    let disabled: HashSet<RuleCode> = lint.disable.into_iter().map(|c| c.0).collect();
+    let severities = lint
+        .severity
+        .into_iter()
+        .map(|(code, severity)| (code.0, severity))
+        .collect();
+
+    if let Some(code) = enabled.iter().find(|code| disabled.contains(code)) {
+        bail!("{code} is both enabled and disabled");
+    }
+    // A `[lint.naming]` section turns the naming rules on, unless some of
+    // them are enabled by code.
+    if lint.naming.is_some() && !naming_rules().any(|code| enabled.contains(&code)) {
+        enabled.extend(naming_rules());
+    }
+    let defaults = NamingConventions::default();
+    let naming = match lint.naming {
+        Some(section) => NamingConventions {
+            operators: section.operators.map_or(defaults.operators, |c| c.0),
+            variables: section.variables.map_or(defaults.variables, |c| c.0),
+            constants: section.constants.map_or(defaults.constants, |c| c.0),
+            invariant_prefixes: section
+                .invariant_prefixes
+                .unwrap_or(defaults.invariant_prefixes),
+        },
+        None => defaults,
+    };
+
+    Ok(LintOptions {
+        naming,
+        rules: RuleSettings {
+            enabled,
+            disabled,
+            severities,
+        },
+        translator: None,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    lint: Option<LintSection>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintSection {
+    #[serde(default)]
+    enable: Vec<Code>,
+    #[serde(default)]
+    disable: Vec<Code>,
+    #[serde(default)]
+    severity: HashMap<Code, Severity>,
+    naming: Option<NamingSection>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NamingSection {
+    operators: Option<CaseName>,
+    variables: Option<CaseName>,
+    constants: Option<CaseName>,
+    invariant_prefixes: Option<Vec<String>>,
+}
+
+/// A rule code, with a did-you-mean hint when it is misspelled.
+#[derive(PartialEq, Eq, Hash)]
+struct Code(RuleCode);
+
+impl<'de> Deserialize<'de> for Code {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        registry::parse_code(&name)
+            .map(Code)
+            .map_err(de::Error::custom)
+    }
+}
+
+struct CaseName(Case);
+
+impl<'de> Deserialize<'de> for CaseName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map(CaseName).map_err(|()| {
+            de::Error::custom(format!(
+                "unknown case `{name}`; expected \"PascalCase\", \"camelCase\", \
+                 \"UPPER_CASE\", \"snake_case\" or \"any\""
+            ))
+        })
+    }
+}
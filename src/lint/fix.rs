@@ -0,0 +1,48 @@
+use crate::lint::types::{Diagnostic, Edit};
+
+/// Applies the fixes of `diags` to `src`, returning the new source and how
+/// many fixes were applied. A fix whose edits overlap those of an earlier
+/// fix is left out; re-linting the result offers it again.
+pub fn apply(src: &str, diags: &[Diagnostic]) -> (String, usize) {
+    let mut accepted: Vec<&Edit> = Vec::new();
+    let mut applied = 0;
+    for fix in diags.iter().filter_map(|d| d.fix.as_ref()) {
+        let valid = fix.edits.iter().all(|e| {
+            e.start <= e.end
+                && e.end <= src.len()
+                && src.is_char_boundary(e.start)
+                && src.is_char_boundary(e.end)
+        });
+        let clashes = fix
+            .edits
+            .iter()
+            .any(|e| accepted.iter().any(|a| overlaps(a, e)));
+        if valid && !clashes && !fix.edits.is_empty() {
+            accepted.extend(&fix.edits);
+            applied += 1;
+        }
+    }
+
+    // Insertions at the same offset keep the order of their diagnostics.
+    accepted.sort_by_key(|e| (e.start, e.end));
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in accepted {
+        out.push_str(&src[pos..edit.start]);
+        out.push_str(&edit.replacement);
+        pos = edit.end;
+    }
+    out.push_str(&src[pos..]);
+    (out, applied)
+}
+
+/// Whether two edits touch the same bytes. Insertions only clash with an
+/// edit that replaces the text around them.
+fn overlaps(a: &Edit, b: &Edit) -> bool {
+    match (a.start == a.end, b.start == b.end) {
+        (true, true) => false,
+        (true, false) => b.start < a.start && a.start < b.end,
+        (false, true) => a.start < b.start && b.start < a.end,
+        (false, false) => a.start < b.end && b.start < a.end,
+    }
+}
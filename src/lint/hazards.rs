@@ -0,0 +1,266 @@
+use crate::lint::FileContext;
+use crate::lint::actions;
+use crate::lint::types::{Diagnostic, RuleCode};
+use crate::semantic::{SymbolKind, SymbolTable, node_for_range};
+use tree_sitter::{Node, Tree};
+
+/// Why TLC cannot, or should not, enumerate a set.
+enum Size {
+    Finite,
+    /// Finite, but at least doubly exponential in its parts.
+    Huge,
+    Infinite,
+}
+
+/// Operators TLC evaluates without a `.cfg` naming them.
+const MODEL_ROOTS: &[&str] = &["Init", "Next", "Spec"];
+
+/// Reports expressions that make TLC enumerate infinite or doubly
+/// exponential sets. These are legal TLA+, so they are warnings: the spec
+/// may only ever be checked by TLAPS or with a model that overrides them.
+///
+/// Only definitions TLC evaluates are checked: `Init`, `Next`, `Spec`,
+/// whatever the `.cfg` files or sibling modules refer to, and what those
+/// call. Theorems, proofs and helpers only used by them are left alone.
+pub fn check_hazards(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut report = |node: Node, message: String| {
+        diags.push(ctx.diagnostic(node.byte_range(), RuleCode::TLA021, message));
+    };
+    let text = |node: Node| node.utf8_text(ctx.src.as_bytes()).unwrap_or_default();
+
+    let external = &ctx.external;
+    let roots = symbols
+        .module_symbols()
+        .filter(|(_, s)| s.kind == SymbolKind::Operator)
+        .filter(|(_, s)| {
+            MODEL_ROOTS.contains(&s.name.as_str())
+                || external.config.operators.contains(&s.name)
+                || external.modules.contains(&s.name)
+        })
+        .map(|(id, _)| id);
+    let mut stack = actions::reachable_definitions(tree, symbols, roots);
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+
+        if is_powerset(node) && node.child_by_field_name("rhs").is_some_and(is_powerset) {
+            report(
+                node,
+                "`SUBSET SUBSET` has 2^(2^n) elements; TLC enumerates it even for a 3-element set"
+                    .to_string(),
+            );
+            continue;
+        }
+
+        let (set, what) = match node.kind() {
+            // Functions over infinite domains are fine: TLC evaluates them
+            // lazily, one argument at a time.
+            "quantifier_bound" => {
+                let what = match node.parent().map(|p| p.kind()) {
+                    Some("bounded_quantification") => "Quantifier",
+                    Some("set_filter" | "set_map") => "Set comprehension",
+                    _ => continue,
+                };
+                (node.child_by_field_name("set"), what)
+            }
+            "choose" => (node.child_by_field_name("set"), "CHOOSE"),
+            _ => continue,
+        };
+        let Some(set) = set else {
+            continue;
+        };
+        match size(tree, symbols, set, 0) {
+            Size::Infinite => report(
+                set,
+                format!(
+                    "{what} ranges over infinite set `{}`; TLC cannot enumerate it, {}",
+                    text(set),
+                    hint(tree, symbols, set)
+                ),
+            ),
+            Size::Huge => report(
+                set,
+                format!(
+                    "{what} ranges over `{}`, which is doubly exponential in size; TLC will enumerate all of it",
+                    text(set)
+                ),
+            ),
+            Size::Finite => {}
+        }
+    }
+
+    // Init is evaluated by enumerating every value its `\in` conjuncts allow.
+    if let Some((init, _)) = actions::find_operator(tree, symbols, "Init") {
+        let memberships = actions::reachable_matching(
+            tree,
+            symbols,
+            init,
+            |node| node.kind() == "bound_infix_op" && actions::op_symbol(node) == Some("in"),
+            |_| false,
+        );
+        for membership in memberships {
+            let Some(set) = membership.child_by_field_name("rhs") else {
+                continue;
+            };
+            if set.kind() == "set_filter" {
+                // Its generator is reported above.
+                continue;
+            }
+            match size(tree, symbols, set, 0) {
+                Size::Infinite => report(
+                    set,
+                    format!(
+                        "`Init` draws from infinite set `{}`; TLC cannot enumerate the initial states, {}",
+                        text(set),
+                        hint(tree, symbols, set)
+                    ),
+                ),
+                Size::Huge => report(
+                    set,
+                    format!(
+                        "`Init` draws from `{}`, which is doubly exponential in size; TLC generates one initial state per element",
+                        text(set)
+                    ),
+                ),
+                Size::Finite => {}
+            }
+        }
+    }
+}
+
+/// How to make an infinite `set` enumerable.
+fn hint(tree: &Tree, symbols: &SymbolTable, set: Node) -> &'static str {
+    let mut set = set;
+    while let Some(inner) = match set.kind() {
+        "parentheses" => set.named_child(0),
+        "identifier_ref" => symbols
+            .resolve(set)
+            .map(|id| symbols.symbol(id))
+            .filter(|sym| sym.kind == SymbolKind::Operator && sym.arity == 0)
+            .and_then(|sym| {
+                node_for_range(
+                    tree.root_node(),
+                    &sym.definition_range,
+                    "operator_definition",
+                )
+            })
+            .and_then(|def| def.child_by_field_name("definition")),
+        _ => None,
+    } {
+        set = inner;
+    }
+    match set.kind() {
+        "nat_number_set" | "int_number_set" => "use a finite range such as 0..N",
+        "bound_op" => "bound the sequence length, e.g. UNION {[1..n -> S] : n \\in 0..N}",
+        _ => "restrict it to a finite set or override it in the model",
+    }
+}
+
+fn is_powerset(node: Node) -> bool {
+    node.kind() == "bound_prefix_op" && actions::op_symbol(node) == Some("powerset")
+}
+
+/// A syntactic estimate of how large a set expression is. Zero-argument
+/// operators of this file are followed, up to a small depth.
+fn size(tree: &Tree, symbols: &SymbolTable, node: Node, depth: usize) -> Size {
+    const MAX_DEPTH: usize = 8;
+    let part = |child: Option<Node>| match child {
+        Some(child) if depth < MAX_DEPTH => size(tree, symbols, child, depth + 1),
+        _ => Size::Finite,
+    };
+
+    match node.kind() {
+        "nat_number_set" | "int_number_set" | "real_number_set" | "string_set" => Size::Infinite,
+        "parentheses" => part(node.named_child(0)),
+        "bound_op" => {
+            let is_seq = node
+                .child_by_field_name("name")
+                .and_then(|name| symbols.resolve(name))
+                .is_some_and(|id| {
+                    let sym = symbols.symbol(id);
+                    sym.kind == SymbolKind::Imported && sym.name == "Seq"
+                });
+            if is_seq { Size::Infinite } else { Size::Finite }
+        }
+        "identifier_ref" => {
+            let Some(id) = symbols.resolve(node) else {
+                return Size::Finite;
+            };
+            let sym = symbols.symbol(id);
+            if sym.kind != SymbolKind::Operator || sym.arity != 0 {
+                return Size::Finite;
+            }
+            let body = node_for_range(
+                tree.root_node(),
+                &sym.definition_range,
+                "operator_definition",
+            )
+            .and_then(|def| def.child_by_field_name("definition"));
+            part(body)
+        }
+        "bound_prefix_op" if is_powerset(node) => match part(node.child_by_field_name("rhs")) {
+            Size::Finite => Size::Finite,
+            Size::Huge => Size::Huge,
+            Size::Infinite => Size::Infinite,
+        },
+        "set_of_functions" => {
+            let mut cursor = node.walk();
+            let sets: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter(|c| c.kind() != "maps_to")
+                .collect();
+            let sizes: Vec<Size> = sets.iter().map(|s| part(Some(*s))).collect();
+            if sizes.iter().any(|s| matches!(s, Size::Infinite)) {
+                Size::Infinite
+            } else if sets
+                .first()
+                .is_some_and(|domain| is_powerset(*domain) || domain.kind() == "set_of_functions")
+            {
+                Size::Huge
+            } else {
+                Size::Finite
+            }
+        }
+        "set_of_records" | "set_filter" => {
+            let mut cursor = node.walk();
+            let parts: Vec<Node> = node.named_children(&mut cursor).collect();
+            let sets = parts.into_iter().filter_map(|c| {
+                if c.kind() == "quantifier_bound" {
+                    c.child_by_field_name("set")
+                } else if c.kind() == "identifier" || Some(c) == node.child_by_field_name("filter")
+                {
+                    None
+                } else {
+                    Some(c)
+                }
+            });
+            let mut result = Size::Finite;
+            for set in sets {
+                match part(Some(set)) {
+                    Size::Infinite => return Size::Infinite,
+                    Size::Huge => result = Size::Huge,
+                    Size::Finite => {}
+                }
+            }
+            result
+        }
+        "bound_infix_op" => match actions::op_symbol(node) {
+            Some("times" | "cup") => {
+                let lhs = part(node.child_by_field_name("lhs"));
+                let rhs = part(node.child_by_field_name("rhs"));
+                match (lhs, rhs) {
+                    (Size::Infinite, _) | (_, Size::Infinite) => Size::Infinite,
+                    (Size::Huge, _) | (_, Size::Huge) => Size::Huge,
+                    _ => Size::Finite,
+                }
+            }
+            _ => Size::Finite,
+        },
+        _ => Size::Finite,
+    }
+}
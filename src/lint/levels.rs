@@ -0,0 +1,376 @@
+use crate::lint::FileContext;
+use crate::lint::actions;
+use crate::lint::types::{Diagnostic, RuleCode};
+use crate::semantic::{SymbolId, SymbolKind, SymbolTable, node_for_range};
+use std::collections::HashMap;
+use std::fmt;
+use tree_sitter::{Node, Tree};
+
+/// The level of a TLA+ expression: what it may depend on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Depends only on constants.
+    Constant,
+    /// Depends on the current state (unprimed variables).
+    State,
+    /// Depends on a pair of states (primes, UNCHANGED, `[A]_v`).
+    Action,
+    /// A formula over behaviors (`[]`, `<>`, `~>`, fairness).
+    Temporal,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Constant => "constant",
+            Level::State => "state",
+            Level::Action => "action",
+            Level::Temporal => "temporal",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Level inference over one parsed module, memoized per definition.
+///
+/// Operator parameters count as constants, so a call is as high as the
+/// definition's body or its highest argument. Names imported from other
+/// modules are taken to be constant, which can only under-report.
+pub struct Levels<'a> {
+    tree: &'a Tree,
+    symbols: &'a SymbolTable,
+    memo: HashMap<SymbolId, Level>,
+    visiting: Vec<SymbolId>,
+}
+
+impl<'a> Levels<'a> {
+    pub fn new(tree: &'a Tree, symbols: &'a SymbolTable) -> Self {
+        Self {
+            tree,
+            symbols,
+            memo: HashMap::new(),
+            visiting: Vec::new(),
+        }
+    }
+
+    pub fn of(&mut self, node: Node) -> Level {
+        match node.kind() {
+            "identifier_ref" => self.of_reference(node),
+            "bound_postfix_op" if actions::is_prime(node) => Level::Action.max(self.children(node)),
+            "bound_prefix_op" => match actions::op_symbol(node) {
+                Some("unchanged") => Level::Action.max(self.children(node)),
+                Some("enabled") => Level::State,
+                Some("always" | "eventually") => Level::Temporal,
+                _ => self.children(node),
+            },
+            "bound_infix_op" => match actions::op_symbol(node) {
+                Some("leads_to" | "plus_arrow") => Level::Temporal,
+                _ => self.children(node),
+            },
+            "fairness" => Level::Temporal,
+            "step_expr_or_stutter" | "step_expr_no_stutter" => {
+                Level::Action.max(self.children(node))
+            }
+            "unbounded_quantification" if is_temporal_quantifier(node) => Level::Temporal,
+            "let_in" => node
+                .child_by_field_name("expression")
+                .map_or(Level::Constant, |body| self.of(body)),
+            "operator_definition"
+            | "function_definition"
+            | "module_definition"
+            | "recursive_declaration" => Level::Constant,
+            _ => self.children(node),
+        }
+    }
+
+    /// The level of a module-level or LET definition.
+    pub fn of_symbol(&mut self, id: SymbolId) -> Level {
+        if let Some(level) = self.memo.get(&id) {
+            return *level;
+        }
+        if self.visiting.contains(&id) {
+            // Recursive definitions: the other branches decide.
+            return Level::Constant;
+        }
+
+        let sym = self.symbols.symbol(id);
+        let root = self.tree.root_node();
+        let body = match sym.kind {
+            SymbolKind::Operator => {
+                node_for_range(root, &sym.definition_range, "operator_definition")
+                    .and_then(|def| def.child_by_field_name("definition"))
+            }
+            SymbolKind::Function => {
+                node_for_range(root, &sym.definition_range, "function_definition")
+            }
+            _ => None,
+        };
+        let Some(body) = body else {
+            return Level::Constant;
+        };
+
+        self.visiting.push(id);
+        // A function is as high as its domain and body together.
+        let level = if body.kind() == "function_definition" {
+            self.children(body)
+        } else {
+            self.of(body)
+        };
+        self.visiting.pop();
+        self.memo.insert(id, level);
+        level
+    }
+
+    fn of_reference(&mut self, node: Node) -> Level {
+        let Some(id) = self.symbols.resolve(node) else {
+            return Level::Constant;
+        };
+        let sym = self.symbols.symbol(id);
+        match sym.kind {
+            SymbolKind::Variable => Level::State,
+            SymbolKind::Operator | SymbolKind::Function => self.of_symbol(id),
+            SymbolKind::BoundVariable => {
+                let root = self.tree.root_node();
+                let range = sym.definition_range.clone();
+                if let Some(bound) = node_for_range(root, &range, "quantifier_bound") {
+                    bound
+                        .child_by_field_name("set")
+                        .map_or(Level::Constant, |set| self.of(set))
+                } else if let Some(choose) = node_for_range(root, &range, "choose") {
+                    choose
+                        .child_by_field_name("set")
+                        .map_or(Level::Constant, |set| self.of(set))
+                } else if node_for_range(root, &range, "unbounded_quantification")
+                    .is_some_and(is_temporal_quantifier)
+                {
+                    // `\EE x` and `\AA x` introduce flexible variables.
+                    Level::State
+                } else {
+                    Level::Constant
+                }
+            }
+            _ => Level::Constant,
+        }
+    }
+
+    fn children(&mut self, node: Node) -> Level {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        children
+            .into_iter()
+            .map(|child| self.of(child))
+            .max()
+            .unwrap_or(Level::Constant)
+    }
+}
+
+fn is_temporal_quantifier(node: Node) -> bool {
+    node.child_by_field_name("quantifier")
+        .is_some_and(|q| matches!(q.kind(), "temporal_exists" | "temporal_forall"))
+}
+
+/// `[]`, `<>`, `~>`, `-+->`, fairness and temporal quantifiers.
+fn is_temporal_operator(node: Node) -> bool {
+    match node.kind() {
+        "bound_prefix_op" => matches!(actions::op_symbol(node), Some("always" | "eventually")),
+        "bound_infix_op" => matches!(actions::op_symbol(node), Some("leads_to" | "plus_arrow")),
+        "fairness" => true,
+        "unbounded_quantification" => is_temporal_quantifier(node),
+        _ => false,
+    }
+}
+
+fn is_enabled(node: Node) -> bool {
+    node.kind() == "bound_prefix_op" && actions::op_symbol(node) == Some("enabled")
+}
+
+/// Reports expressions whose level does not fit where they are used:
+/// operands of `'`, UNCHANGED, ENABLED and `[A]_v`, ASSUME bodies and the
+/// arguments of CONSTANT operators, the bodies of `Init`, `Next` and of
+/// configured invariants.
+pub fn check_levels(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut levels = Levels::new(tree, symbols);
+    let mut report = |start: usize, end: usize, message: String| {
+        diags.push(ctx.diagnostic(start..end, RuleCode::TLA008, message));
+    };
+
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+
+        let (what, expects, operands, max): (String, &str, Vec<Node>, Level) = match node.kind() {
+            // A primed action is already reported as a double prime.
+            "bound_postfix_op" if actions::is_prime(node) => {
+                let operand = node.child_by_field_name("lhs");
+                if operand.is_some_and(contains_prime) {
+                    continue;
+                }
+                (
+                    "`'`".into(),
+                    "an operand",
+                    operand.into_iter().collect(),
+                    Level::State,
+                )
+            }
+            "bound_prefix_op" => match actions::op_symbol(node) {
+                Some("unchanged") => (
+                    "UNCHANGED".into(),
+                    "an operand",
+                    node.child_by_field_name("rhs").into_iter().collect(),
+                    Level::State,
+                ),
+                Some("enabled") => (
+                    "ENABLED".into(),
+                    "an operand",
+                    node.child_by_field_name("rhs").into_iter().collect(),
+                    Level::Action,
+                ),
+                _ => continue,
+            },
+            "step_expr_or_stutter" | "step_expr_no_stutter" => {
+                let mut cursor = node.walk();
+                let parts: Vec<Node> = node
+                    .named_children(&mut cursor)
+                    .filter(|c| !matches!(c.kind(), "langle_bracket" | "rangle_bracket_sub"))
+                    .collect();
+                if let [action, subscript] = parts[..] {
+                    let subscript_level = levels.of(subscript);
+                    if subscript_level > Level::State {
+                        report(
+                            subscript.start_byte(),
+                            subscript.end_byte(),
+                            format!(
+                                "Subscript of `[A]_v` must be a state function, found {subscript_level} level"
+                            ),
+                        );
+                    }
+                    ("`[A]_v`".into(), "an operand", vec![action], Level::Action)
+                } else {
+                    continue;
+                }
+            }
+            // SANY evaluates assumptions before any state exists.
+            "assumption" => {
+                let name = node.child_by_field_name("name");
+                let mut cursor = node.walk();
+                let body = node
+                    .named_children(&mut cursor)
+                    .filter(|c| Some(*c) != name && c.kind() != "def_eq")
+                    .last();
+                (
+                    "ASSUME".into(),
+                    "a formula",
+                    body.into_iter().collect(),
+                    Level::Constant,
+                )
+            }
+            "bound_op" => {
+                let Some(name) = node.child_by_field_name("name") else {
+                    continue;
+                };
+                let declared = symbols.resolve(name).map(|id| symbols.symbol(id));
+                let Some(op) = declared.filter(|s| s.kind == SymbolKind::Constant) else {
+                    continue;
+                };
+                let mut cursor = node.walk();
+                let args = node
+                    .children_by_field_name("parameter", &mut cursor)
+                    .filter(|p| p.is_named())
+                    .collect();
+                (
+                    format!("CONSTANT operator `{}`", op.name),
+                    "arguments",
+                    args,
+                    Level::Constant,
+                )
+            }
+            _ => continue,
+        };
+        for operand in operands {
+            let level = levels.of(operand);
+            if level > max {
+                report(
+                    operand.start_byte(),
+                    operand.end_byte(),
+                    format!("{what} expects {expects} of at most {max} level, found {level} level"),
+                );
+            }
+        }
+    }
+
+    if let Some((id, _)) = actions::find_operator(tree, symbols, "Init") {
+        let level = levels.of_symbol(id);
+        if level > Level::State && !explained_by_primes(tree, symbols, id, level) {
+            let range = &symbols.symbol(id).name_range;
+            report(
+                range.start,
+                range.end,
+                format!(
+                    "`Init` has {level} level; the initial predicate must be a state predicate"
+                ),
+            );
+        }
+    }
+
+    if let Some((id, _)) = actions::find_operator(tree, symbols, "Next") {
+        let offending = actions::reachable_matching(
+            tree,
+            symbols,
+            id,
+            |node| is_temporal_operator(node) || is_enabled(node),
+            |_| false,
+        );
+        for node in offending {
+            let message = if is_enabled(node) {
+                "`ENABLED` inside `Next`; TLC cannot evaluate it in the next-state relation"
+                    .to_string()
+            } else {
+                "Temporal formula inside `Next`, which must be an action".to_string()
+            };
+            report(node.start_byte(), node.end_byte(), message);
+        }
+    }
+
+    let mut invariants: Vec<&String> = ctx.external.config.invariants.iter().collect();
+    invariants.sort();
+    for name in invariants {
+        let Some((id, _)) = actions::find_operator(tree, symbols, name) else {
+            continue;
+        };
+        let level = levels.of_symbol(id);
+        if level <= Level::State || explained_by_primes(tree, symbols, id, level) {
+            continue;
+        }
+        let hint = if level == Level::Action {
+            format!("check it as PROPERTY [][{name}]_vars instead")
+        } else {
+            "check it as a PROPERTY instead".to_string()
+        };
+        let range = &symbols.symbol(id).name_range;
+        report(
+            range.start,
+            range.end,
+            format!(
+                "Invariant `{name}` has {level} level; INVARIANT requires a state predicate, {hint}"
+            ),
+        );
+    }
+}
+
+/// Primes in state predicates are reported by TLA007 already.
+fn explained_by_primes(tree: &Tree, symbols: &SymbolTable, id: SymbolId, level: Level) -> bool {
+    level == Level::Action && !actions::primes_reachable_from(tree, symbols, id).is_empty()
+}
+
+fn contains_prime(node: Node) -> bool {
+    if actions::is_prime(node) {
+        return true;
+    }
+    (0..node.named_child_count()).any(|i| node.named_child(i).is_some_and(contains_prime))
+}
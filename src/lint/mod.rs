@@ -1,18 +1,56 @@
-use crate::lint::types::{Diagnostic, RuleCode, Severity};
+use crate::lint::config::{Discovery, RuleSettings};
+use crate::lint::naming::NamingConventions;
+use crate::lint::types::{Diagnostic, Label, RuleCode, Severity};
+use crate::lint::workspace::{ExternalUses, Imports, Workspace};
 use crate::tla_parser::TlaParser;
+use crate::tooling::PcalTranslator;
 use anyhow::{Result, anyhow};
+use std::collections::HashMap;
 use std::fs;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use walkdir::WalkDir;
 
+mod actions;
+pub mod cfg;
+pub mod config;
+pub mod fix;
+mod hazards;
+pub mod levels;
+pub mod naming;
+mod pluscal;
+pub mod registry;
 pub mod reporter;
 pub mod rules;
+mod stdlib;
+mod suppress;
 pub mod types;
+pub mod workspace;
+
+/// Settings that change what the rules report.
+#[derive(Clone, Debug, Default)]
+pub struct LintOptions {
+    /// Options of the naming-convention rules (TLA015-TLA018), which only
+    /// run when `rules` enables them.
+    pub naming: NamingConventions,
+    /// Enabled and disabled rules, and severity overrides.
+    pub rules: RuleSettings,
+    /// Checks PlusCal translations against the translator's output (TLA024)
+    /// when set; otherwise they are only compared by structure.
+    pub translator: Option<PcalTranslator>,
+}
 
 #[derive(Debug)]
 pub struct FileContext {
     pub path: PathBuf,
     pub src: String,
+    /// Names imported through EXTENDS/INSTANCE, filled in by the workspace.
+    pub imports: Imports,
+    /// References from sibling modules and TLC configs, filled in by the
+    /// workspace.
+    pub external: ExternalUses,
+    pub options: LintOptions,
     line_starts: Vec<usize>,
 }
 
@@ -27,6 +65,9 @@ impl FileContext {
         Self {
             path,
             src,
+            imports: Imports::default(),
+            external: ExternalUses::default(),
+            options: LintOptions::default(),
             line_starts,
         }
     }
@@ -41,9 +82,54 @@ impl FileContext {
         let col_chars = self.src[line_start..byte_offset].chars().count() + 1;
         (line_idx + 1, col_chars)
     }
+
+    /// A diagnostic for the bytes `range` of this file, with the default
+    /// severity of `code` and without labels, notes, help or fix.
+    pub fn diagnostic(&self, range: Range<usize>, code: RuleCode, message: String) -> Diagnostic {
+        let (line, column) = self.position(range.start);
+        let (end_line, end_column) = self.position(range.end);
+        Diagnostic {
+            file: self.path.to_string_lossy().into_owned(),
+            line,
+            column,
+            end_line: Some(end_line),
+            end_column: Some(end_column),
+            start_byte: Some(range.start),
+            end_byte: Some(range.end),
+            severity: registry::rule(code).severity,
+            code,
+            message,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+            fix: None,
+        }
+    }
+
+    /// A secondary span pointing at the bytes `range` of this file.
+    pub fn label(&self, range: Range<usize>, message: impl Into<String>) -> Label {
+        let (line, column) = self.position(range.start);
+        let (end_line, end_column) = self.position(range.end);
+        Label {
+            line,
+            column,
+            end_line,
+            end_column,
+            start_byte: range.start,
+            end_byte: range.end,
+            message: message.into(),
+        }
+    }
 }
 
-pub fn run(paths: Vec<PathBuf>, json: bool) -> Result<()> {
+pub fn run(paths: Vec<PathBuf>, json: bool, fix: bool) -> Result<()> {
+    if fix {
+        let applied = apply_fixes(paths.clone())?;
+        eprintln!(
+            "Applied {applied} fix{}",
+            if applied == 1 { "" } else { "es" }
+        );
+    }
     let diagnostics = collect_diagnostics(paths)?;
 
     if json {
@@ -59,30 +145,147 @@ pub fn run(paths: Vec<PathBuf>, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Lints `paths`, configuring each file by the nearest `tla.toml` above it.
 pub fn collect_diagnostics(paths: Vec<PathBuf>) -> Result<Vec<Diagnostic>> {
+    let mut discovery = Discovery::default();
+    collect(paths, |path| discovery.options_for(path))
+}
+
+/// Lints `paths` with the same `options` for every file, ignoring `tla.toml`.
+pub fn collect_diagnostics_with(
+    paths: Vec<PathBuf>,
+    options: &LintOptions,
+) -> Result<Vec<Diagnostic>> {
+    let options = Rc::new(options.clone());
+    collect(paths, |_| Ok(Rc::clone(&options)))
+}
+
+/// Prints every rule with its metadata, as a table or JSON.
+pub fn list_rules(json: bool) -> Result<()> {
+    if json {
+        println!("{}", reporter::rules_json(registry::RULES)?);
+    } else {
+        print!("{}", reporter::rules_table(registry::RULES));
+    }
+    Ok(())
+}
+
+/// Prints the long-form description of the rule `code`.
+pub fn explain(code: &str) -> Result<()> {
+    let code = registry::parse_code(code).map_err(|e| anyhow!(e))?;
+    print!("{}", reporter::explanation(registry::rule(code)));
+    Ok(())
+}
+
+/// Applies the fixes offered by the diagnostics of `paths` and rewrites
+/// the changed files, returning how many fixes were applied. Files are
+/// re-linted after each round so that fixes skipped for overlapping an
+/// earlier one get another chance; a round that leaves any linted file
+/// with an error it did not have is undone, and fixing stops there.
+pub fn apply_fixes(paths: Vec<PathBuf>) -> Result<usize> {
+    const MAX_ROUNDS: usize = 4;
+    let mut diagnostics = collect_diagnostics(paths.clone())?;
+    let mut total = 0;
+    for _ in 0..MAX_ROUNDS {
+        let mut by_file: Vec<(&str, Vec<Diagnostic>)> = Vec::new();
+        for d in diagnostics.iter().filter(|d| d.fix.is_some()) {
+            match by_file.iter_mut().find(|(file, _)| *file == d.file) {
+                Some((_, diags)) => diags.push(d.clone()),
+                None => by_file.push((&d.file, vec![d.clone()])),
+            }
+        }
+
+        let mut originals = Vec::new();
+        let mut applied_this_round = 0;
+        for (file, diags) in by_file {
+            let src = fs::read_to_string(file)?;
+            let (fixed, applied) = fix::apply(&src, &diags);
+            if applied == 0 || fixed == src {
+                continue;
+            }
+            fs::write(file, fixed)?;
+            originals.push((file.to_string(), src));
+            applied_this_round += applied;
+        }
+        if applied_this_round == 0 {
+            break;
+        }
+
+        let relinted = collect_diagnostics(paths.clone())?;
+        if adds_errors(&diagnostics, &relinted) {
+            for (file, src) in originals {
+                fs::write(file, src)?;
+            }
+            break;
+        }
+        total += applied_this_round;
+        diagnostics = relinted;
+    }
+    Ok(total)
+}
+
+/// Whether `after` has an error that `before` did not, matched by file,
+/// code and message since fixes move the errors they leave in place.
+fn adds_errors(before: &[Diagnostic], after: &[Diagnostic]) -> bool {
+    let mut known: HashMap<(&str, RuleCode, &str), usize> = HashMap::new();
+    for d in before.iter().filter(|d| d.severity == Severity::Error) {
+        *known.entry((&d.file, d.code, &d.message)).or_default() += 1;
+    }
+    after
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .any(
+            |d| match known.get_mut(&(d.file.as_str(), d.code, d.message.as_str())) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            },
+        )
+}
+
+fn collect<F>(paths: Vec<PathBuf>, mut options_for: F) -> Result<Vec<Diagnostic>>
+where
+    F: FnMut(&Path) -> Result<Rc<LintOptions>>,
+{
     let files = collect_tla_files(paths);
     let mut parser = TlaParser::new()?;
+    let mut workspace = Workspace::new()?;
     let mut diagnostics = Vec::new();
 
     for path in files {
+        let options = options_for(&path)?;
         let src = fs::read_to_string(&path)?;
-        let tree = match parser.parse(&src) {
-            Some(t) => t,
-            None => {
-                diagnostics.push(Diagnostic {
-                    file: path.to_string_lossy().into_owned(),
-                    line: 0,
-                    column: 0,
-                    severity: Severity::Error,
-                    code: RuleCode::TLA000,
-                    message: "Failed to parse TLA+ file".to_string(),
-                });
-                continue;
+        let mut file_diagnostics = Vec::new();
+        match parser.parse(&src) {
+            Some(tree) => {
+                let mut ctx = FileContext::new(path.clone(), src);
+                ctx.imports = workspace.imports(&ctx.path, &ctx.src, &tree);
+                ctx.external = workspace.external_uses(&ctx.path, &ctx.src, &tree);
+                ctx.options = (*options).clone();
+                let ran = rules::run_all_rules(&ctx, &tree, &mut file_diagnostics);
+                suppress::apply(&ctx, &tree, &ran, &mut file_diagnostics);
             }
-        };
-
-        let ctx = FileContext::new(path.clone(), src);
-        rules::run_all_rules(&ctx, &tree, &mut diagnostics);
+            None => file_diagnostics.push(Diagnostic {
+                file: path.to_string_lossy().into_owned(),
+                line: 0,
+                column: 0,
+                end_line: None,
+                end_column: None,
+                start_byte: None,
+                end_byte: None,
+                severity: registry::rule(RuleCode::TLA000).severity,
+                code: RuleCode::TLA000,
+                message: "Failed to parse TLA+ file".to_string(),
+                labels: Vec::new(),
+                notes: Vec::new(),
+                help: None,
+                fix: None,
+            }),
+        }
+        options.rules.apply(&mut file_diagnostics);
+        diagnostics.extend(file_diagnostics);
     }
 
     Ok(diagnostics)
@@ -95,7 +298,10 @@ fn collect_tla_files(paths: Vec<PathBuf>) -> Vec<PathBuf> {
             let walker = WalkDir::new(&path)
                 .follow_links(false)
                 .into_iter()
-                .filter_entry(|e| !is_hidden_entry(e) && !e.file_type().is_symlink());
+                .filter_entry(|e| {
+                    // The root itself may be `.` or `..`.
+                    (e.depth() == 0 || !is_hidden_entry(e)) && !e.file_type().is_symlink()
+                });
             for entry in walker.filter_map(Result::ok) {
                 let p = entry.path();
                 if is_tla_file(p) {
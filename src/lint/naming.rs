@@ -0,0 +1,91 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifier styles a naming convention can require.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Case {
+    /// `TypeOK`, `SendMsg`: leading capital, no underscores.
+    Pascal,
+    /// `msgs`, `pcState`: leading lowercase letter, no underscores.
+    Camel,
+    /// `N`, `MAX_PROCS`: no lowercase letters.
+    Upper,
+    /// `msgs`, `pc_state`: no uppercase letters.
+    Snake,
+    /// Accept any name.
+    Any,
+}
+
+impl Case {
+    pub fn matches(self, name: &str) -> bool {
+        let Some(first) = name.chars().next() else {
+            return true;
+        };
+        match self {
+            Case::Pascal => first.is_ascii_uppercase() && !name.contains('_'),
+            Case::Camel => first.is_ascii_lowercase() && !name.contains('_'),
+            Case::Upper => !name.chars().any(|c| c.is_ascii_lowercase()),
+            Case::Snake => !name.chars().any(|c| c.is_ascii_uppercase()),
+            Case::Any => true,
+        }
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Case::Pascal => "PascalCase",
+            Case::Camel => "camelCase",
+            Case::Upper => "UPPER_CASE",
+            Case::Snake => "snake_case",
+            Case::Any => "any case",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Case {
+    type Err = ();
+
+    /// Parses the names [`Case`] displays as, plus `any`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Case::Pascal,
+            Case::Camel,
+            Case::Upper,
+            Case::Snake,
+            Case::Any,
+        ]
+        .into_iter()
+        .find(|case| case.to_string() == s || (*case == Case::Any && s == "any"))
+        .ok_or(())
+    }
+}
+
+/// A project's naming style guide. By default operators are PascalCase,
+/// variables camelCase and constants UPPER_CASE, and invariants start with
+/// `TypeOK` or `Inv`. `vars`, the customary tuple of all variables, passes
+/// as an operator name, and the PlusCal translation is never checked since
+/// its names are chosen by the translator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamingConventions {
+    /// Module-level operator and function definitions.
+    pub operators: Case,
+    /// VARIABLES.
+    pub variables: Case,
+    /// CONSTANTS.
+    pub constants: Case,
+    /// Operators a `.cfg` checks as INVARIANT must start with one of these.
+    pub invariant_prefixes: Vec<String>,
+}
+
+impl Default for NamingConventions {
+    fn default() -> Self {
+        Self {
+            operators: Case::Pascal,
+            variables: Case::Camel,
+            constants: Case::Upper,
+            invariant_prefixes: vec!["TypeOK".to_string(), "Inv".to_string()],
+        }
+    }
+}
@@ -0,0 +1,836 @@
+use crate::lint::FileContext;
+use crate::lint::types::{Diagnostic, RuleCode};
+use crate::tooling::PcalTranslator;
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+const BEGIN_MARKER: &str = "BEGIN TRANSLATION";
+const END_MARKER: &str = "END TRANSLATION";
+
+/// Variables the translator adds on its own: the program counter and the
+/// procedure call stack.
+const TRANSLATOR_VARIABLES: &[&str] = &["pc", "stack"];
+
+/// The `(* --algorithm ... *)` block of a module, if it has one.
+pub fn algorithm(tree: &Tree) -> Option<Node<'_>> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let module = root.children(&mut cursor).find(|n| n.kind() == "module")?;
+    let mut cursor = module.walk();
+    let comments: Vec<Node> = module
+        .children(&mut cursor)
+        .filter(|n| n.kind() == "block_comment")
+        .collect();
+    comments.into_iter().find_map(|comment| {
+        (0..comment.named_child_count())
+            .filter_map(|i| comment.named_child(i))
+            .find(|n| n.kind() == "pcal_algorithm")
+    })
+}
+
+/// Byte range between the `BEGIN TRANSLATION` and `END TRANSLATION`
+/// markers, when the module has exactly one pair of them.
+pub fn translation_range(ctx: &FileContext, tree: &Tree) -> Option<Range<usize>> {
+    let (units, begins, ends) = markers(ctx, tree.root_node())?;
+    match (&begins[..], &ends[..]) {
+        ([begin], [end]) if begin < end => Some(units[*begin].end_byte()..units[*end].start_byte()),
+        _ => None,
+    }
+}
+
+/// Top-level units of the module, with the indices of the begin and end
+/// markers among them.
+fn markers<'t>(
+    ctx: &FileContext,
+    root: Node<'t>,
+) -> Option<(Vec<Node<'t>>, Vec<usize>, Vec<usize>)> {
+    let mut cursor = root.walk();
+    let module = root.children(&mut cursor).find(|n| n.kind() == "module")?;
+    let mut cursor = module.walk();
+    let units: Vec<Node> = module.children(&mut cursor).collect();
+    let is_marker = |n: Node, marker: &str| {
+        n.kind() == "comment"
+            && n.utf8_text(ctx.src.as_bytes())
+                .unwrap_or_default()
+                .contains(marker)
+    };
+    let begins = (0..units.len())
+        .filter(|i| is_marker(units[*i], BEGIN_MARKER))
+        .collect();
+    let ends = (0..units.len())
+        .filter(|i| is_marker(units[*i], END_MARKER))
+        .collect();
+    Some((units, begins, ends))
+}
+
+/// All nodes of `kind` below `node`, in source order.
+fn descendants<'t>(node: Node<'t>, kind: &str) -> Vec<Node<'t>> {
+    let mut out = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node.kind() == kind {
+            out.push(node);
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    out.sort_by_key(|n| n.start_byte());
+    out
+}
+
+/// Labels of the algorithm's statements.
+pub fn labels(algorithm: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    let mut stack = vec![algorithm];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        out.extend(
+            node.children_by_field_name("label", &mut cursor)
+                .filter(|n| n.kind() == "identifier"),
+        );
+        stack.extend(node.named_children(&mut cursor));
+    }
+    out.sort_by_key(|n| n.start_byte());
+    out
+}
+
+/// Global, process-local and procedure variables, including procedure
+/// parameters: everything the translation declares as a VARIABLE.
+pub fn variables(algorithm: Node) -> Vec<Node> {
+    let mut out: Vec<Node> = descendants(algorithm, "pcal_var_decl")
+        .into_iter()
+        .chain(descendants(algorithm, "pcal_proc_var_decl"))
+        .filter_map(|decl| decl.named_child(0))
+        .filter(|n| n.kind() == "identifier")
+        .collect();
+    out.sort_by_key(|n| n.start_byte());
+    out
+}
+
+/// What the translator must have turned into top-level definitions: the
+/// `define` block, processes and procedures.
+fn translated_definitions(algorithm: Node) -> Vec<Node> {
+    let defines = descendants(algorithm, "pcal_definitions")
+        .into_iter()
+        .flat_map(|block| {
+            let mut cursor = block.walk();
+            block
+                .named_children(&mut cursor)
+                .filter_map(|def| def.child_by_field_name("name"))
+                .filter(|n| n.kind() == "identifier")
+                .collect::<Vec<_>>()
+        });
+    let units = descendants(algorithm, "pcal_process")
+        .into_iter()
+        .chain(descendants(algorithm, "pcal_proc_decl"))
+        .filter_map(|unit| unit.child_by_field_name("name"));
+    let mut out: Vec<Node> = defines.chain(units).collect();
+    out.sort_by_key(|n| n.start_byte());
+    out
+}
+
+/// The translator's own label names, added by `-label` to algorithms
+/// written without labels.
+fn is_generated_label(name: &str) -> bool {
+    name.strip_prefix("Lbl_")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Top-level facts of the translation region.
+#[derive(Default)]
+struct Translation {
+    variables: BTreeSet<String>,
+    definitions: BTreeSet<String>,
+    /// Definitions of the form `L == pc = "L" /\ ...`, one per label.
+    labels: BTreeSet<String>,
+}
+
+impl Translation {
+    fn collect(ctx: &FileContext, units: &[Node]) -> Self {
+        let text = |n: Node| {
+            n.utf8_text(ctx.src.as_bytes())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mut out = Self::default();
+        for unit in units {
+            match unit.kind() {
+                "variable_declaration" => {
+                    let mut cursor = unit.walk();
+                    out.variables.extend(
+                        unit.named_children(&mut cursor)
+                            .filter(|n| n.kind() == "identifier")
+                            .map(text),
+                    );
+                }
+                "operator_definition" | "function_definition" => {
+                    let Some(name) = unit.child_by_field_name("name") else {
+                        continue;
+                    };
+                    let name = text(name);
+                    let quoted = format!("\"{name}\"");
+                    let is_label = descendants(*unit, "bound_infix_op").into_iter().any(|op| {
+                        let lhs = op.child_by_field_name("lhs");
+                        let lhs = match lhs {
+                            Some(f) if f.kind() == "function_evaluation" => f.named_child(0),
+                            other => other,
+                        };
+                        op.child_by_field_name("symbol")
+                            .is_some_and(|s| s.kind() == "eq")
+                            && lhs.is_some_and(|l| l.kind() == "identifier_ref" && text(l) == "pc")
+                            && op
+                                .child_by_field_name("rhs")
+                                .is_some_and(|r| r.kind() == "string" && text(r) == quoted)
+                    });
+                    if is_label {
+                        out.labels.insert(name.clone());
+                    }
+                    out.definitions.insert(name);
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+}
+
+/// Reports PlusCal algorithms whose translation is missing or does not match
+/// the algorithm any more.
+///
+/// With a translator configured, the module is translated afresh in a
+/// scratch directory and its translation compared line by line with the one
+/// in the module, along with the `chksum(pcal)` and `chksum(tla)` values the
+/// translator records in the `BEGIN TRANSLATION` comment. Without one, or
+/// when it fails, the translation is only compared with the algorithm
+/// structurally: every variable, label, process, procedure and `define` must
+/// have its counterpart in the translation, and the translation must not
+/// have variables or label actions the algorithm lacks. The diagnostic then
+/// says that edits inside statements are not detected.
+pub fn check_translation(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
+    let mut report = |node: Node, message: String| {
+        diags.push(ctx.diagnostic(node.byte_range(), RuleCode::TLA024, message));
+    };
+    let text = |n: Node| n.utf8_text(ctx.src.as_bytes()).unwrap_or_default();
+
+    let Some(algorithm) = algorithm(tree) else {
+        return;
+    };
+    let Some((units, begins, ends)) = markers(ctx, tree.root_node()) else {
+        return;
+    };
+
+    let name = algorithm.child_by_field_name("name").unwrap_or(algorithm);
+    let (begin, end) = match (&begins[..], &ends[..]) {
+        ([], []) => {
+            report(
+                name,
+                format!(
+                    "PlusCal algorithm `{}` has no translation; run the PlusCal translator",
+                    text(name)
+                ),
+            );
+            return;
+        }
+        ([begin], [end]) if begin < end => (*begin, *end),
+        ([_, extra, ..], _) => {
+            report(
+                units[*extra],
+                "Duplicate `BEGIN TRANSLATION` marker".to_string(),
+            );
+            return;
+        }
+        (_, [_, extra, ..]) => {
+            report(
+                units[*extra],
+                "Duplicate `END TRANSLATION` marker".to_string(),
+            );
+            return;
+        }
+        ([begin], _) => {
+            report(
+                units[*begin],
+                "`BEGIN TRANSLATION` has no matching `END TRANSLATION` after it".to_string(),
+            );
+            return;
+        }
+        ([], [end]) => {
+            report(
+                units[*end],
+                "`END TRANSLATION` has no matching `BEGIN TRANSLATION`".to_string(),
+            );
+            return;
+        }
+    };
+
+    let help = match &ctx.options.translator {
+        Some(translator) => match translator_differences(ctx, translator, &units, begin, end) {
+            Ok(stale) => {
+                report_stale(ctx, diags, name, units[begin], stale, None);
+                return;
+            }
+            Err(err) => format!("the PlusCal translator failed ({err:#}), so {STRUCTURE_ONLY}"),
+        },
+        None => format!("TLA_TOOLS_JAR is not set, so {STRUCTURE_ONLY}"),
+    };
+
+    let translation = Translation::collect(ctx, &units[begin + 1..end]);
+    let mut stale = Vec::new();
+    let mut algorithm_variables = BTreeSet::new();
+    for var in variables(algorithm) {
+        let var = text(var);
+        if !translation.variables.contains(var) {
+            stale.push(format!("variable `{var}` is not declared"));
+        }
+        algorithm_variables.insert(var);
+    }
+    for var in &translation.variables {
+        if !algorithm_variables.contains(var.as_str())
+            && !TRANSLATOR_VARIABLES.contains(&var.as_str())
+        {
+            stale.push(format!("variable `{var}` is no longer in the algorithm"));
+        }
+    }
+    let mut algorithm_labels = BTreeSet::new();
+    for label in labels(algorithm) {
+        let label = text(label);
+        if !translation.labels.contains(label) {
+            stale.push(format!("label `{label}` has no action"));
+        }
+        algorithm_labels.insert(label);
+    }
+    for label in &translation.labels {
+        if !algorithm_labels.contains(label.as_str()) && !is_generated_label(label) {
+            stale.push(format!("action `{label}` has no label in the algorithm"));
+        }
+    }
+    for def in translated_definitions(algorithm) {
+        let def = text(def);
+        if !translation.definitions.contains(def) {
+            stale.push(format!("`{def}` is not defined"));
+        }
+    }
+    report_stale(ctx, diags, name, units[begin], stale, Some(help));
+}
+
+const STRUCTURE_ONLY: &str = "only variables, labels, processes, procedures and \
+    definitions were compared; edits inside statements are not detected. Set \
+    TLA_TOOLS_JAR to compare the translation with the translator's output";
+
+/// Reports an out-of-date translation at its `BEGIN TRANSLATION` marker,
+/// unless `stale` is empty.
+fn report_stale(
+    ctx: &FileContext,
+    diags: &mut Vec<Diagnostic>,
+    name: Node,
+    marker: Node,
+    stale: Vec<String>,
+    help: Option<String>,
+) {
+    if stale.is_empty() {
+        return;
+    }
+    const SHOWN: usize = 3;
+    let mut details = stale[..stale.len().min(SHOWN)].join(", ");
+    if stale.len() > SHOWN {
+        details.push_str(&format!(" and {} more", stale.len() - SHOWN));
+    }
+    // The message summarizes; notes list every difference.
+    diags.push(Diagnostic {
+        labels: vec![ctx.label(name.byte_range(), "translated from this algorithm")],
+        notes: stale,
+        help,
+        ..ctx.diagnostic(
+            marker.byte_range(),
+            RuleCode::TLA024,
+            format!("PlusCal translation is out of date: {details}; re-run the PlusCal translator"),
+        )
+    });
+}
+
+/// Differences between the translation between `units[begin]` and
+/// `units[end]` and the one the translator writes for the module now.
+fn translator_differences(
+    ctx: &FileContext,
+    translator: &PcalTranslator,
+    units: &[Node],
+    begin: usize,
+    end: usize,
+) -> Result<Vec<String>> {
+    let file_name = ctx
+        .path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} has no file name", ctx.path.display()))?;
+    let fresh = translator.translate(file_name, &ctx.src)?;
+    let (fresh_marker, theirs) =
+        translation_lines(&fresh).ok_or_else(|| anyhow!("it wrote no translation"))?;
+    let marker = units[begin]
+        .utf8_text(ctx.src.as_bytes())
+        .unwrap_or_default();
+
+    let mut stale = Vec::new();
+    let changed = |which: &str| match (checksum(marker, which), checksum(fresh_marker, which)) {
+        (Some(old), Some(new)) if old != new => {
+            Some(format!("chksum({which}) was \"{old}\", now \"{new}\""))
+        }
+        _ => None,
+    };
+    if let Some(change) = changed("pcal") {
+        stale.push(format!(
+            "the algorithm changed since it was translated ({change})"
+        ));
+    } else if let Some(change) = changed("tla") {
+        stale.push(format!("the translation was edited by hand ({change})"));
+    }
+
+    // Rows of the module, counted from the line of the begin marker.
+    let first_row = units[begin].end_position().row + 1;
+    let region = &ctx.src[units[begin].end_byte()..units[end].start_byte()];
+    let ours: Vec<(usize, &str)> = region
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (first_row + i, line.trim_end()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let differing = ours.iter().zip(&theirs).find(|((_, a), b)| a != *b);
+    match differing {
+        Some(((row, a), b)) => stale.push(format!(
+            "line {row} is `{}` but the translator writes `{}`",
+            a.trim(),
+            b.trim()
+        )),
+        None if ours.len() > theirs.len() => stale.push(format!(
+            "the translator writes nothing from line {} on",
+            ours[theirs.len()].0
+        )),
+        None if ours.len() < theirs.len() => stale.push(format!(
+            "the translator writes `{}` after the last line",
+            theirs[ours.len()].trim()
+        )),
+        None => {}
+    }
+    Ok(stale)
+}
+
+/// The `BEGIN TRANSLATION` line of a translated module and the non-blank
+/// lines up to its `END TRANSLATION` line.
+fn translation_lines(src: &str) -> Option<(&str, Vec<&str>)> {
+    let mut lines = src.lines().map(str::trim_end);
+    let marker = lines.find(|line| line.contains(BEGIN_MARKER))?;
+    let mut body = Vec::new();
+    for line in lines {
+        if line.contains(END_MARKER) {
+            return Some((marker, body));
+        }
+        if !line.is_empty() {
+            body.push(line);
+        }
+    }
+    None
+}
+
+/// The `chksum(pcal)` or `chksum(tla)` value recorded in a `BEGIN
+/// TRANSLATION` comment, as in `chksum(pcal) = "a4e7c4a4"`.
+fn checksum<'s>(marker: &'s str, which: &str) -> Option<&'s str> {
+    let key = format!("chksum({which})");
+    let rest = &marker[marker.find(&key)? + key.len()..];
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    rest.strip_prefix('"')?.split('"').next()
+}
+
+/// Statement node kinds; a nested `pcal_algorithm_body` is a C-syntax block.
+const STATEMENTS: &[&str] = &[
+    "pcal_algorithm_body",
+    "pcal_assert",
+    "pcal_assign",
+    "pcal_await",
+    "pcal_either",
+    "pcal_goto",
+    "pcal_if",
+    "pcal_macro_call",
+    "pcal_print",
+    "pcal_proc_call",
+    "pcal_return",
+    "pcal_skip",
+    "pcal_while",
+    "pcal_with",
+];
+
+/// A statement with the label written in front of it, if any.
+#[derive(Clone, Copy)]
+struct Stmt<'t> {
+    node: Node<'t>,
+    label: Option<Node<'t>>,
+}
+
+/// The statement sequences directly inside `node`: the branches of an `if`
+/// or `either`, or the single body of anything else. C-syntax blocks are
+/// flattened into the sequence they appear in.
+fn branches(node: Node) -> Vec<Vec<Stmt>> {
+    let mut out = vec![Vec::new()];
+    let mut label = None;
+    let mut cursor = node.walk();
+    if !cursor.goto_first_child() {
+        return out;
+    }
+    loop {
+        let child = cursor.node();
+        if cursor.field_name() == Some("label") {
+            if child.kind() == "identifier" {
+                label = Some(child);
+            }
+        } else if matches!(child.kind(), "elsif" | "else" | "or") && !child.is_named() {
+            out.push(Vec::new());
+        } else if child.kind() == "pcal_algorithm_body" {
+            let mut block = branches(child).swap_remove(0);
+            if let Some(first) = block.first_mut() {
+                first.label = first.label.or(label.take());
+            }
+            out.last_mut().expect("at least one branch").extend(block);
+        } else if STATEMENTS.contains(&child.kind()) {
+            out.last_mut().expect("at least one branch").push(Stmt {
+                node: child,
+                label: label.take(),
+            });
+        }
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+    // `if` without `else` falls through when the condition is false.
+    if node.kind() == "pcal_if" && out.len() == 1 {
+        out.push(Vec::new());
+    }
+    out
+}
+
+/// Whether control leaves the current step somewhere inside `node`, so the
+/// statement after it must start a new one.
+fn ends_step(node: Node) -> bool {
+    let mut cursor = node.walk();
+    if node
+        .children_by_field_name("label", &mut cursor)
+        .next()
+        .is_some()
+    {
+        return true;
+    }
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|child| {
+            matches!(child.kind(), "pcal_proc_call" | "pcal_return" | "pcal_goto")
+                || ends_step(child)
+        })
+}
+
+/// Assignment targets of a `pcal_assign`, one per `||`-separated part.
+fn assigned_variables(assign: Node) -> Vec<Node> {
+    let mut cursor = assign.walk();
+    assign
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "pcal_lhs")
+        .filter_map(|lhs| lhs.named_child(0))
+        .filter(|n| matches!(n.kind(), "identifier" | "identifier_ref"))
+        .collect()
+}
+
+/// Variables assigned between two labels so far.
+#[derive(Clone, Default)]
+struct Step<'t> {
+    assigned: Vec<(String, Node<'t>)>,
+}
+
+impl<'t> Step<'t> {
+    fn first_assignment(&self, var: &str) -> Option<Node<'t>> {
+        self.assigned
+            .iter()
+            .find(|(name, _)| name == var)
+            .map(|(_, node)| *node)
+    }
+
+    fn merge(&mut self, other: Step<'t>) {
+        for (name, node) in other.assigned {
+            if self.first_assignment(&name).is_none() {
+                self.assigned.push((name, node));
+            }
+        }
+    }
+}
+
+/// Checks the statements of one process, procedure, macro or uniprocess
+/// body.
+struct Checker<'a, 't> {
+    ctx: &'a FileContext,
+    diags: &'a mut Vec<Diagnostic>,
+    /// Variables the body may assign.
+    declared: BTreeSet<&'a str>,
+    /// Non-parameter variables each macro assigns.
+    macros: &'a HashMap<&'a str, Vec<Node<'t>>>,
+    /// Off for macros and for uniprocess algorithms without any labels,
+    /// which the translator labels itself.
+    check_labels: bool,
+}
+
+impl<'a, 't> Checker<'a, 't> {
+    fn report(&mut self, node: Node, code: RuleCode, message: String) -> &mut Diagnostic {
+        self.diags
+            .push(self.ctx.diagnostic(node.byte_range(), code, message));
+        self.diags.last_mut().expect("just pushed")
+    }
+
+    fn text(&self, node: Node) -> &'a str {
+        node.utf8_text(self.ctx.src.as_bytes()).unwrap_or_default()
+    }
+
+    /// The keyword a statement starts with, to anchor diagnostics on.
+    fn keyword(node: Node) -> Node {
+        match node.kind() {
+            "pcal_assign" => node.named_child(0).unwrap_or(node),
+            "pcal_macro_call" | "pcal_proc_call" => {
+                node.child_by_field_name("name").unwrap_or(node)
+            }
+            _ => node.child(0).unwrap_or(node),
+        }
+    }
+
+    fn missing_label(&mut self, node: Node, message: String) {
+        self.report(Self::keyword(node), RuleCode::TLA025, message);
+    }
+
+    fn body(&mut self, unit: &str, body: Node<'t>) {
+        let stmts = branches(body).swap_remove(0);
+        if let Some(first) = stmts.first() {
+            if self.check_labels && first.label.is_none() {
+                self.missing_label(
+                    first.node,
+                    format!("First statement of {unit} must be labeled"),
+                );
+            }
+        }
+        self.sequence(&stmts, Step::default());
+    }
+
+    fn sequence(&mut self, stmts: &[Stmt<'t>], mut step: Step<'t>) -> Step<'t> {
+        let mut previous: Option<Node> = None;
+        for stmt in stmts {
+            let node = stmt.node;
+            if stmt.label.is_some() {
+                step = Step::default();
+            } else if self.check_labels {
+                if node.kind() == "pcal_while" {
+                    self.missing_label(node, "`while` must be labeled".to_string());
+                } else if let Some(after) = previous.and_then(|p| label_required_after(p, node)) {
+                    self.missing_label(node, format!("Statement after {after} must be labeled"));
+                }
+            }
+
+            match node.kind() {
+                "pcal_assign" => {
+                    for var in assigned_variables(node) {
+                        let name = self.text(var);
+                        if !self.declared.contains(name) {
+                            self.report(
+                                var,
+                                RuleCode::TLA027,
+                                format!("Assignment to undeclared variable `{name}`"),
+                            );
+                        }
+                        self.assign(&mut step, name, var);
+                    }
+                }
+                "pcal_macro_call" => {
+                    let name = node
+                        .child_by_field_name("name")
+                        .map(|n| self.text(n))
+                        .unwrap_or_default();
+                    let macros = self.macros;
+                    for var in macros.get(name).into_iter().flatten() {
+                        self.assign(&mut step, self.text(*var), node);
+                    }
+                }
+                "pcal_await" if !step.assigned.is_empty() => {
+                    self.report(
+                        Self::keyword(node),
+                        RuleCode::TLA028,
+                        "`await` follows an assignment in the same step; the whole step, \
+                         assignment included, waits for the condition. Label the `await` to \
+                         make it a step of its own"
+                            .to_string(),
+                    );
+                }
+                "pcal_if" | "pcal_either" => {
+                    let mut merged = Step::default();
+                    for branch in branches(node) {
+                        merged.merge(self.sequence(&branch, step.clone()));
+                    }
+                    step = merged;
+                }
+                // The loop test and the code after the loop share a step;
+                // the body is the other branch.
+                "pcal_while" => {
+                    for branch in branches(node) {
+                        self.sequence(&branch, step.clone());
+                    }
+                }
+                "pcal_with" => {
+                    for branch in branches(node) {
+                        step = self.sequence(&branch, step);
+                    }
+                }
+                "pcal_proc_call" | "pcal_return" | "pcal_goto" => step = Step::default(),
+                _ => {}
+            }
+            previous = Some(node);
+        }
+        step
+    }
+
+    fn assign(&mut self, step: &mut Step<'t>, name: &str, at: Node<'t>) {
+        if let Some(first) = step.first_assignment(name) {
+            let (line, col) = self.ctx.position(first.start_byte());
+            let label = self.ctx.label(first.byte_range(), "first assigned here");
+            self.report(
+                at,
+                RuleCode::TLA026,
+                format!(
+                    "`{name}` is assigned twice in one step (first at {line}:{col}); \
+                     add a label before the second assignment"
+                ),
+            )
+            .labels
+            .push(label);
+        } else {
+            step.assigned.push((name.to_string(), at));
+        }
+    }
+}
+
+/// Why `next` must be labeled when it directly follows `previous`.
+fn label_required_after(previous: Node, next: Node) -> Option<&'static str> {
+    match previous.kind() {
+        "pcal_proc_call" if !matches!(next.kind(), "pcal_return" | "pcal_goto") => Some("a `call`"),
+        "pcal_return" => Some("`return`"),
+        "pcal_goto" => Some("`goto`"),
+        "pcal_if" if ends_step(previous) => {
+            Some("an `if` that contains a label, `call`, `return` or `goto`")
+        }
+        "pcal_either" if ends_step(previous) => {
+            Some("an `either` that contains a label, `call`, `return` or `goto`")
+        }
+        _ => None,
+    }
+}
+
+/// Variables declared by the `pcal_var_decl`s or `pcal_proc_var_decl`s
+/// directly below `node`.
+fn declared_in<'t>(ctx: &'t FileContext, node: Node) -> Vec<&'t str> {
+    let mut out = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "pcal_var_decls" | "pcal_proc_var_decls" => {
+                out.extend(declared_in(ctx, child));
+            }
+            "pcal_var_decl" | "pcal_proc_var_decl" => {
+                out.extend(
+                    child
+                        .named_child(0)
+                        .filter(|n| n.kind() == "identifier")
+                        .and_then(|n| n.utf8_text(ctx.src.as_bytes()).ok()),
+                );
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Reports what the PlusCal translator would reject: missing labels, a
+/// variable assigned twice in one step, assignments to undeclared
+/// variables. Also warns about `await` after an assignment in the same
+/// step, which blocks the assignment too.
+pub fn check_algorithm(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
+    let Some(algorithm) = algorithm(tree) else {
+        return;
+    };
+    let text = |n: Node| n.utf8_text(ctx.src.as_bytes()).unwrap_or_default();
+    let mut cursor = algorithm.walk();
+    let units: Vec<Node> = algorithm.named_children(&mut cursor).collect();
+    let globals = declared_in(ctx, algorithm);
+    // Macro bodies are expanded in place, so they may assign anything.
+    let everything: BTreeSet<&str> = globals
+        .iter()
+        .copied()
+        .chain(variables(algorithm).into_iter().map(text))
+        .collect();
+
+    let mut macro_bodies = Vec::new();
+    let mut macros: HashMap<&str, Vec<Node>> = HashMap::new();
+    for unit in units.iter().filter(|u| u.kind() == "pcal_macro") {
+        let Some(decl) = unit.named_child(0) else {
+            continue;
+        };
+        let mut cursor = decl.walk();
+        let params: BTreeSet<&str> = decl
+            .children_by_field_name("parameter", &mut cursor)
+            .map(text)
+            .collect();
+        let assigned = descendants(*unit, "pcal_assign")
+            .into_iter()
+            .flat_map(assigned_variables)
+            .filter(|var| !params.contains(text(*var)))
+            .collect();
+        if let Some(name) = decl.child_by_field_name("name") {
+            macros.insert(text(name), assigned);
+        }
+        let mut declared = everything.clone();
+        declared.extend(params);
+        macro_bodies.extend(unit.named_child(1).map(|body| (body, declared)));
+    }
+
+    let mut checker = Checker {
+        ctx,
+        diags,
+        declared: BTreeSet::new(),
+        macros: &macros,
+        check_labels: false,
+    };
+    for (body, declared) in macro_bodies {
+        checker.declared = declared;
+        checker.sequence(&branches(body).swap_remove(0), Step::default());
+    }
+
+    let labeled = !labels(algorithm).is_empty();
+    for unit in &units {
+        let (what, decl, body) = match unit.kind() {
+            "pcal_procedure" => ("procedure", unit.named_child(0), unit.named_child(1)),
+            "pcal_process" => (
+                "process",
+                Some(*unit),
+                unit.named_child(unit.named_child_count().saturating_sub(1)),
+            ),
+            "pcal_algorithm_body" => {
+                checker.declared = globals.iter().copied().collect();
+                checker.check_labels = labeled;
+                checker.body("the algorithm", *unit);
+                continue;
+            }
+            _ => continue,
+        };
+        let (Some(decl), Some(body)) = (decl, body) else {
+            continue;
+        };
+        let name = decl
+            .child_by_field_name("name")
+            .map(text)
+            .unwrap_or_default();
+        checker.declared = globals
+            .iter()
+            .copied()
+            .chain(declared_in(ctx, decl))
+            .collect();
+        checker.check_labels = true;
+        checker.body(&format!("{what} `{name}`"), body);
+    }
+}
@@ -0,0 +1,463 @@
+use crate::lint::types::{RuleCode, Severity};
+use serde::Serialize;
+use std::fmt;
+
+/// What a rule is about, for grouping in `--list-rules`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Syntax,
+    Correctness,
+    Specification,
+    Unused,
+    Style,
+    Naming,
+    Performance,
+    PlusCal,
+    Suppression,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::Syntax => "syntax",
+            Category::Correctness => "correctness",
+            Category::Specification => "specification",
+            Category::Unused => "unused",
+            Category::Style => "style",
+            Category::Naming => "naming",
+            Category::Performance => "performance",
+            Category::PlusCal => "pluscal",
+            Category::Suppression => "suppression",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What every lint rule declares about itself.
+#[derive(Debug, Serialize)]
+pub struct Rule {
+    #[serde(skip)]
+    pub code: RuleCode,
+    /// Short kebab-case name.
+    pub name: &'static str,
+    pub category: Category,
+    /// Severity of its diagnostics unless `tla.toml` overrides it.
+    pub severity: Severity,
+    /// Opt-in rules only report once enabled in `tla.toml`.
+    pub enabled_by_default: bool,
+    /// One line, shown by `--list-rules`.
+    pub summary: &'static str,
+    /// Why the rule exists and how to resolve it, shown by `--explain`.
+    pub explanation: &'static str,
+    /// TLA+ snippets the rule reports.
+    pub examples: &'static [&'static str],
+}
+
+/// The metadata of `code`.
+pub fn rule(code: RuleCode) -> &'static Rule {
+    &RULES[code as usize]
+}
+
+/// Parses a rule code, with a hint when only the case is wrong.
+pub fn parse_code(name: &str) -> Result<RuleCode, String> {
+    if let Ok(code) = name.parse() {
+        return Ok(code);
+    }
+    let first = RuleCode::ALL.first().expect("rule codes");
+    let last = RuleCode::ALL.last().expect("rule codes");
+    let mut message = format!("unknown rule code `{name}`; codes run from {first} to {last}");
+    if let Ok(code) = name.to_ascii_uppercase().parse::<RuleCode>() {
+        message.push_str(&format!(", did you mean `{code}`?"));
+    }
+    Err(message)
+}
+
+/// Every rule, indexed by its code.
+pub const RULES: &[Rule] = &[
+    Rule {
+        code: RuleCode::TLA000,
+        name: "syntax-error",
+        category: Category::Syntax,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "The file does not parse as TLA+",
+        explanation: "The parser could not make sense of part of the file. SANY and TLC \
+            reject the module, and most other rules are skipped because error recovery \
+            can swallow whole definitions. The diagnostic points at the unexpected token \
+            and names what was expected: the closing delimiter, the `ELSE` of an `IF`, \
+            the `IN` of a `LET`, the expression after an operator, or the `====` line \
+            that ends the module.",
+        examples: &["Init == x = (0"],
+    },
+    Rule {
+        code: RuleCode::TLA001,
+        name: "unused-variable",
+        category: Category::Unused,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A state variable is declared but never used",
+        explanation: "A variable nothing refers to is still part of every state TLC \
+            explores, and TLC reports it as unassigned in Init. Usually it is left over \
+            from an earlier version of the spec. Uses in sibling modules that EXTEND or \
+            INSTANCE the module count. `--fix` removes it from its VARIABLES \
+            declaration.",
+        examples: &["VARIABLES x, y\nInit == x = 0\nNext == x' = x + 1"],
+    },
+    Rule {
+        code: RuleCode::TLA002,
+        name: "missing-init-next",
+        category: Category::Specification,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A module with variables has no Init or Next",
+        explanation: "TLC looks up `Init` and `Next` by name when no configuration says \
+            otherwise, and a spec with state needs both: Init describes the initial \
+            states and Next the steps between them. `--fix` inserts skeletons to fill \
+            in.",
+        examples: &["VARIABLES x\nInit == x = 0"],
+    },
+    Rule {
+        code: RuleCode::TLA003,
+        name: "undefined-identifier",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A name is used but never defined or imported, or before its definition",
+        explanation: "SANY rejects references to names that are not defined in the \
+            module, declared as a constant or variable, bound locally, or imported \
+            through EXTENDS or INSTANCE. Check for typos and missing EXTENDS. A name \
+            must also be defined above its first use, in the module and in LET; only \
+            operators listed in a RECURSIVE declaration may be used earlier.",
+        examples: &[
+            "Next == x' = Len(queue)   \\* without EXTENDS Sequences",
+            "Init == x = Start\nStart == 0",
+        ],
+    },
+    Rule {
+        code: RuleCode::TLA004,
+        name: "unused-operator",
+        category: Category::Unused,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "An operator is defined but never used",
+        explanation: "Operators nothing refers to, that are not entry points such as Init, \
+            Next or Spec, and that no TLC configuration or sibling module mentions, are \
+            usually dead code or a sign that something was meant to use them.",
+        examples: &["Helper == 42\nNext == x' = x + 1"],
+    },
+    Rule {
+        code: RuleCode::TLA005,
+        name: "unused-constant",
+        category: Category::Unused,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A constant is declared but never used",
+        explanation: "A constant that no expression, ASSUME, model or extending module \
+            uses still has to be given a value in every model. Remove it or use it.",
+        examples: &["CONSTANTS N, Unused\nInit == x \\in 1..N"],
+    },
+    Rule {
+        code: RuleCode::TLA006,
+        name: "unchanged-variables",
+        category: Category::Specification,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "An action neither assigns nor leaves UNCHANGED some variables",
+        explanation: "Every step must determine the next value of every variable. TLC only \
+            notices an action that leaves one unspecified when it evaluates that action, \
+            which may be deep into a run. Assign the variables or list them in \
+            UNCHANGED; `--fix` does the latter.",
+        examples: &["VARIABLES x, y\nInc == x' = x + 1\nNext == Inc"],
+    },
+    Rule {
+        code: RuleCode::TLA007,
+        name: "prime-misuse",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A prime is applied where there is no next state",
+        explanation: "Only state variables, and expressions over them, have a next-state \
+            value. Priming a constant or a bound variable, priming twice, or priming \
+            inside Init or an invariant, which are evaluated on a single state, is an \
+            error.",
+        examples: &["CONSTANT N\nNext == N' = N", "Init == x' = 0"],
+    },
+    Rule {
+        code: RuleCode::TLA008,
+        name: "level-mismatch",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "An expression has the wrong level for where it is used",
+        explanation: "TLA+ distinguishes constant, state, action and temporal formulas. \
+            ASSUME bodies and the arguments of CONSTANT operators must be constant, \
+            Init and invariants must be state predicates, and Next must be an action \
+            without temporal operators or ENABLED. SANY or TLC reject the spec \
+            otherwise; an action invariant can be checked as the property \
+            `[][Inv]_vars` instead.",
+        examples: &["Next == x' = x + 1 /\\ []TypeOK"],
+    },
+    Rule {
+        code: RuleCode::TLA009,
+        name: "spec-shape",
+        category: Category::Specification,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A temporal specification is not of the form Init /\\ [][Next]_vars",
+        explanation: "TLC checks a SPECIFICATION by finding its initial predicate and its \
+            next-state relation. A specification that does not reference Init or Next, \
+            lacks the `[][Next]_vars` conjunct, or whose subscript misses variables \
+            allows steps that change those variables arbitrarily.",
+        examples: &["VARIABLES x, y\nvars == <<x>>\nSpec == Init /\\ [][Next]_vars"],
+    },
+    Rule {
+        code: RuleCode::TLA010,
+        name: "duplicate-definition",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A name is defined or declared twice",
+        explanation: "TLA+ does not allow redefining a name in the same scope, nor \
+            reusing a name that an EXTENDed standard module already defines. SANY \
+            rejects the module.",
+        examples: &["VARIABLES x, x", "EXTENDS Sequences\nLen(s) == 0"],
+    },
+    Rule {
+        code: RuleCode::TLA011,
+        name: "arity-mismatch",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "An operator is called with the wrong number of arguments",
+        explanation: "Operators must be applied to exactly as many arguments as they have \
+            parameters, and operators passed as arguments must take as many arguments \
+            as the parameter they fill.",
+        examples: &["Max(a, b) == IF a > b THEN a ELSE b\nInit == x = Max(1)"],
+    },
+    Rule {
+        code: RuleCode::TLA012,
+        name: "recursive-declaration",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "Recursion without RECURSIVE, or RECURSIVE without a definition",
+        explanation: "A recursive operator must be announced with a RECURSIVE declaration \
+            before its definition, and every RECURSIVE declaration must be followed by a \
+            definition.",
+        examples: &["Fact(n) == IF n = 0 THEN 1 ELSE n * Fact(n - 1)"],
+    },
+    Rule {
+        code: RuleCode::TLA013,
+        name: "module-name",
+        category: Category::Correctness,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "The module name does not match its file",
+        explanation: "SANY and TLC find a module by its file name, so module `Foo` must be \
+            in `Foo.tla`, and a file holds a single top-level module.",
+        examples: &["---- MODULE Counter ----   \\* in counter_v2.tla"],
+    },
+    Rule {
+        code: RuleCode::TLA014,
+        name: "shadowing",
+        category: Category::Style,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A local name shadows an outer definition",
+        explanation: "A parameter, bound variable or LET definition with the name of a \
+            definition in an outer scope hides it, which is easy to misread. SANY \
+            rejects some of these outright. Rename the inner name.",
+        examples: &["VARIABLE x\nInv == \\A x \\in 1..3 : x > 0"],
+    },
+    Rule {
+        code: RuleCode::TLA015,
+        name: "operator-naming",
+        category: Category::Naming,
+        severity: Severity::Warning,
+        enabled_by_default: false,
+        summary: "An operator name does not follow the configured case",
+        explanation: "Enforces the `operators` case of `[lint.naming]` in tla.toml, \
+            PascalCase by default. `vars` is accepted, and names in a PlusCal \
+            translation are left alone.",
+        examples: &["type_ok == x \\in Nat"],
+    },
+    Rule {
+        code: RuleCode::TLA016,
+        name: "variable-naming",
+        category: Category::Naming,
+        severity: Severity::Warning,
+        enabled_by_default: false,
+        summary: "A variable name does not follow the configured case",
+        explanation: "Enforces the `variables` case of `[lint.naming]` in tla.toml, \
+            camelCase by default. Variables a PlusCal translation declares are left \
+            alone.",
+        examples: &["VARIABLE Queue_Length"],
+    },
+    Rule {
+        code: RuleCode::TLA017,
+        name: "constant-naming",
+        category: Category::Naming,
+        severity: Severity::Warning,
+        enabled_by_default: false,
+        summary: "A constant name does not follow the configured case",
+        explanation: "Enforces the `constants` case of `[lint.naming]` in tla.toml, \
+            UPPER_CASE by default.",
+        examples: &["CONSTANT max_size"],
+    },
+    Rule {
+        code: RuleCode::TLA018,
+        name: "invariant-prefix",
+        category: Category::Naming,
+        severity: Severity::Warning,
+        enabled_by_default: false,
+        summary: "An invariant name lacks a configured prefix",
+        explanation: "Operators a TLC configuration checks as INVARIANT must start with one \
+            of the `invariant_prefixes` of `[lint.naming]`, so that invariants are \
+            recognizable in the spec itself.",
+        examples: &["Safe == x <= 10   \\* INVARIANT Safe, with prefixes [\"Inv\"]"],
+    },
+    Rule {
+        code: RuleCode::TLA019,
+        name: "case-without-other",
+        category: Category::Correctness,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A CASE has no OTHER arm",
+        explanation: "When no guard of a CASE holds, its value is unspecified and TLC stops \
+            with an error, possibly only in a rarely reached state. Add an OTHER arm, or \
+            make sure the guards cover every case. For CASEs over predicates or actions \
+            `--fix` adds `[] OTHER -> FALSE`.",
+        examples: &["Sign == CASE x > 0 -> 1\n          [] x < 0 -> -1"],
+    },
+    Rule {
+        code: RuleCode::TLA020,
+        name: "duplicate-case-guard",
+        category: Category::Correctness,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "Two arms of a CASE have the same guard",
+        explanation: "TLC evaluates CASE arms in order, so an arm whose guard repeats an \
+            earlier one is never selected. One of the two guards is usually a typo.",
+        examples: &["CASE x = 0 -> \"zero\"\n  [] x = 0 -> \"none\""],
+    },
+    Rule {
+        code: RuleCode::TLA021,
+        name: "state-space-hazard",
+        category: Category::Performance,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "TLC would enumerate an infinite or doubly exponential set",
+        explanation: "Quantifiers, set comprehensions, CHOOSE and the `\\in` conjuncts of \
+            Init make TLC enumerate their sets. Nat, Int, Seq(S) and the like cannot be \
+            enumerated, and SUBSET SUBSET S is astronomically large. Only what TLC \
+            evaluates is checked: Init, Next, Spec, the operators a `.cfg` names and \
+            everything they call, not theorems or proofs. The spec may still be fine \
+            for TLAPS or with a model that overrides the set.",
+        examples: &[
+            "Init == x \\in Nat",
+            "Inv == \\A s \\in Seq(Data) : Len(s) >= 0",
+        ],
+    },
+    Rule {
+        code: RuleCode::TLA022,
+        name: "unused-extends",
+        category: Category::Unused,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A module is EXTENDed but none of its definitions are used",
+        explanation: "EXTENDing a module whose definitions are never used slows down \
+            parsing and invites name clashes. Remove it from EXTENDS.",
+        examples: &["EXTENDS Naturals, Sequences\nInit == x = 0"],
+    },
+    Rule {
+        code: RuleCode::TLA023,
+        name: "unused-instance",
+        category: Category::Unused,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A named INSTANCE is never used",
+        explanation: "A named instance `I == INSTANCE M` whose definitions are never \
+            referred to as `I!Op` is dead code.",
+        examples: &["Chan == INSTANCE Channel\nNext == x' = x + 1"],
+    },
+    Rule {
+        code: RuleCode::TLA024,
+        name: "pluscal-translation",
+        category: Category::PlusCal,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A PlusCal algorithm has no translation, or it is out of date",
+        explanation: "TLC checks the TLA+ translation, not the algorithm, so a missing or \
+            stale translation means checking something other than what is written. With \
+            `TLA_TOOLS_JAR` set, the module is translated again with `pcal.trans` in a \
+            scratch directory and both the translation and the `chksum(pcal)` and \
+            `chksum(tla)` values of the `BEGIN TRANSLATION` line are compared with its \
+            output. Without the jar, the translation is only compared with the \
+            algorithm by its variables, labels, processes, procedures and definitions, \
+            so an edit inside a statement that keeps the same labels and variables is \
+            not detected.",
+        examples: &[
+            "(* --algorithm Counter\nvariables x = 0, y = 0;\n...\n\\* BEGIN TRANSLATION\nVARIABLES x, pc\n...",
+        ],
+    },
+    Rule {
+        code: RuleCode::TLA025,
+        name: "pluscal-missing-label",
+        category: Category::PlusCal,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A PlusCal statement needs a label",
+        explanation: "The translator requires labels at the start of processes and \
+            procedures, on `while` loops, and after `call`, `return`, `goto` and \
+            statements containing labels. It refuses to translate otherwise.",
+        examples: &["begin\n  while x < 3 do\n    x := x + 1;\n  end while;"],
+    },
+    Rule {
+        code: RuleCode::TLA026,
+        name: "pluscal-double-assignment",
+        category: Category::PlusCal,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A variable is assigned twice in one step",
+        explanation: "A labeled step becomes one TLA+ action, which can give each variable \
+            only one next value. Add a label between the two assignments.",
+        examples: &["A: x := 1;\n   x := x + 1;"],
+    },
+    Rule {
+        code: RuleCode::TLA027,
+        name: "pluscal-undeclared-variable",
+        category: Category::PlusCal,
+        severity: Severity::Error,
+        enabled_by_default: true,
+        summary: "A PlusCal statement assigns an undeclared variable",
+        explanation: "Only variables declared by the algorithm, its processes or its \
+            procedures can be assigned. The translator rejects the algorithm.",
+        examples: &["variables x = 0;\nbegin\n  A: y := 1;"],
+    },
+    Rule {
+        code: RuleCode::TLA028,
+        name: "pluscal-await-after-assignment",
+        category: Category::PlusCal,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "An await follows an assignment in the same step",
+        explanation: "A step is atomic, so an `await` later in the step blocks the whole \
+            step, including the assignments before it. That is rarely what was meant; \
+            put a label before the `await` to make it a step of its own.",
+        examples: &["A: x := 1;\n   await y > 0;"],
+    },
+    Rule {
+        code: RuleCode::TLA029,
+        name: "suppression",
+        category: Category::Suppression,
+        severity: Severity::Warning,
+        enabled_by_default: true,
+        summary: "A tla-lint comment is malformed or silences nothing",
+        explanation: "Suppression comments that silence nothing hide future problems and \
+            rot as the spec changes. Remove them, or fix the command or rule code. In a \
+            file with syntax errors, suppressions for rules that only run on a clean \
+            parse are not reported.",
+        examples: &["\\* tla-lint: disable-next-line TLA001\nCONSTANT N   \\* used below"],
+    },
+];
@@ -1,4 +1,5 @@
-use crate::lint::types::{Diagnostic, Severity};
+use crate::lint::registry::Rule;
+use crate::lint::types::{Diagnostic, Fix, Label, Severity};
 use anyhow::Result;
 use serde::Serialize;
 use std::io::{self, Write};
@@ -8,9 +9,25 @@ struct SerializableDiagnostic<'a> {
     file: &'a str,
     line: usize,
     column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_byte: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_byte: Option<usize>,
     severity: &'a str,
     code: String,
     message: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    labels: &'a [Label],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    notes: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<&'a Fix>,
 }
 
 pub fn print_human(diags: &[Diagnostic]) -> Result<()> {
@@ -30,6 +47,19 @@ pub fn print_human(diags: &[Diagnostic]) -> Result<()> {
             format_code(&d.code),
             d.message
         )?;
+        for label in &d.labels {
+            writeln!(
+                out,
+                "    {}:{}:{}: {}",
+                d.file, label.line, label.column, label.message
+            )?;
+        }
+        for note in &d.notes {
+            writeln!(out, "    note: {note}")?;
+        }
+        if let Some(help) = &d.help {
+            writeln!(out, "    help: {help}")?;
+        }
     }
     out.flush()?;
     Ok(())
@@ -48,17 +78,117 @@ pub fn to_json(diags: &[Diagnostic]) -> Result<String> {
             file: &d.file,
             line: d.line,
             column: d.column,
-            severity: match d.severity {
-                Severity::Warning => "warning",
-                Severity::Error => "error",
-            },
+            end_line: d.end_line,
+            end_column: d.end_column,
+            start_byte: d.start_byte,
+            end_byte: d.end_byte,
+            severity: severity_name(d.severity),
             code: format_code(&d.code),
             message: &d.message,
+            labels: &d.labels,
+            notes: &d.notes,
+            help: d.help.as_deref(),
+            fix: d.fix.as_ref(),
         })
         .collect();
     Ok(serde_json::to_string_pretty(&serializable)?)
 }
 
+/// One line per rule: code, name, category, severity and summary.
+pub fn rules_table(rules: &[Rule]) -> String {
+    let name_width = rules.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    let category_width = rules
+        .iter()
+        .map(|r| r.category.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "{:<6}  {:<name_width$}  {:<category_width$}  {:<8}  SUMMARY\n",
+        "CODE", "NAME", "CATEGORY", "SEVERITY"
+    );
+    for rule in rules {
+        let opt_in = if rule.enabled_by_default {
+            ""
+        } else {
+            " (opt-in)"
+        };
+        out.push_str(&format!(
+            "{:<6}  {:<name_width$}  {:<category_width$}  {:<8}  {}{opt_in}\n",
+            format_code(&rule.code),
+            rule.name,
+            rule.category.to_string(),
+            severity_name(rule.severity),
+            rule.summary,
+        ));
+    }
+    out
+}
+
+pub fn rules_json(rules: &[Rule]) -> Result<String> {
+    #[derive(Serialize)]
+    struct JsonRule<'a> {
+        code: String,
+        #[serde(flatten)]
+        rule: &'a Rule,
+    }
+    let rules: Vec<_> = rules
+        .iter()
+        .map(|rule| JsonRule {
+            code: format_code(&rule.code),
+            rule,
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&rules)?)
+}
+
+/// The long-form description of `rule` shown by `--explain`.
+pub fn explanation(rule: &Rule) -> String {
+    let mut out = format!("{} {}\n", format_code(&rule.code), rule.name);
+    out.push_str(&format!(
+        "Category: {}. Severity: {}.",
+        rule.category,
+        severity_name(rule.severity)
+    ));
+    if !rule.enabled_by_default {
+        out.push_str(" Off unless enabled in tla.toml.");
+    }
+    out.push_str(&format!("\n\n{}.\n\n", rule.summary));
+    out.push_str(&wrap(rule.explanation, 78));
+    for example in rule.examples {
+        out.push_str("\n\nExample:\n");
+        for line in example.lines() {
+            out.push_str(&format!("\n    {line}"));
+        }
+    }
+    out.push('\n');
+    out
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// Breaks `text` into lines of at most `width` characters at spaces.
+fn wrap(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        if line_len > 0 && line_len + 1 + word.len() > width {
+            out.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            out.push(' ');
+            line_len += 1;
+        }
+        out.push_str(word);
+        line_len += word.len();
+    }
+    out
+}
+
 fn format_code(code: &crate::lint::types::RuleCode) -> String {
     format!("{:?}", code)
 }
@@ -1,125 +1,1965 @@
 use crate::lint::FileContext;
-use crate::lint::types::{Diagnostic, RuleCode, Severity};
-use std::collections::{HashMap, HashSet};
+use crate::lint::actions;
+use crate::lint::hazards;
+use crate::lint::levels;
+use crate::lint::naming::NamingConventions;
+use crate::lint::pluscal;
+use crate::lint::stdlib;
+use crate::lint::types::{Diagnostic, Edit, Fix, RuleCode};
+use crate::semantic::{
+    ScopeKind, SymbolId, SymbolKind, SymbolTable, declared_arity, node_for_range, parameters,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
 use tree_sitter::{Node, Tree};
 
-pub fn run_all_rules(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
-    rule_unused_variables(ctx, tree, diags);
-    rule_missing_init_next(ctx, tree, diags);
+/// Operators TLC looks up by name when no config says otherwise.
+const ENTRY_POINTS: &[&str] = &["Init", "Next", "Spec"];
+
+/// One pass over a file, reporting one or more related rules. Rule
+/// metadata lives in [`registry`](crate::lint::registry).
+pub struct Check {
+    pub codes: &'static [RuleCode],
+    /// Error recovery can swallow whole definitions and the references
+    /// inside them, so absence checks only run on a tree without errors.
+    pub needs_clean_tree: bool,
+    pub run: fn(&FileContext, &Tree, &SymbolTable, &mut Vec<Diagnostic>),
 }
 
-fn rule_unused_variables(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
-    let root = tree.root_node();
-    let mut declared: HashMap<String, usize> = HashMap::new();
-    traverse(root, &mut |node| {
-        if is_decl_node(node) {
-            for ident in child_identifiers(node) {
-                if let Ok(text) = ident.utf8_text(ctx.src.as_bytes()) {
-                    declared.insert(text.to_string(), ident.start_byte());
+/// Every check, in the order they run.
+pub const CHECKS: &[Check] = &[
+    Check {
+        codes: &[RuleCode::TLA000],
+        needs_clean_tree: false,
+        run: |ctx, tree, _, diags| rule_syntax_errors(ctx, tree, diags),
+    },
+    Check {
+        codes: &[RuleCode::TLA013],
+        needs_clean_tree: false,
+        run: |ctx, tree, _, diags| rule_module_names(ctx, tree, diags),
+    },
+    Check {
+        codes: &[RuleCode::TLA010],
+        needs_clean_tree: true,
+        run: rule_duplicate_definitions,
+    },
+    Check {
+        codes: &[RuleCode::TLA001],
+        needs_clean_tree: true,
+        run: rule_unused_variables,
+    },
+    Check {
+        codes: &[RuleCode::TLA005],
+        needs_clean_tree: true,
+        run: |ctx, _, symbols, diags| rule_unused_constants(ctx, symbols, diags),
+    },
+    Check {
+        codes: &[RuleCode::TLA002, RuleCode::TLA006],
+        needs_clean_tree: true,
+        run: rule_missing_init_next,
+    },
+    Check {
+        codes: &[RuleCode::TLA009],
+        needs_clean_tree: true,
+        run: rule_spec_shape,
+    },
+    Check {
+        codes: &[RuleCode::TLA004],
+        needs_clean_tree: true,
+        run: rule_unused_operators,
+    },
+    Check {
+        codes: &[RuleCode::TLA022, RuleCode::TLA023],
+        needs_clean_tree: true,
+        run: rule_unused_imports,
+    },
+    Check {
+        codes: &[RuleCode::TLA007],
+        needs_clean_tree: true,
+        run: rule_prime_misuse,
+    },
+    Check {
+        codes: &[RuleCode::TLA011],
+        needs_clean_tree: true,
+        run: rule_operator_arity,
+    },
+    Check {
+        codes: &[RuleCode::TLA012],
+        needs_clean_tree: true,
+        run: |ctx, _, symbols, diags| rule_recursive_declarations(ctx, symbols, diags),
+    },
+    Check {
+        codes: &[RuleCode::TLA014],
+        needs_clean_tree: true,
+        run: |ctx, _, symbols, diags| rule_shadowing(ctx, symbols, diags),
+    },
+    Check {
+        codes: &[RuleCode::TLA019, RuleCode::TLA020],
+        needs_clean_tree: true,
+        run: |ctx, tree, _, diags| rule_case_arms(ctx, tree, diags),
+    },
+    Check {
+        codes: &[RuleCode::TLA021],
+        needs_clean_tree: true,
+        run: hazards::check_hazards,
+    },
+    Check {
+        codes: &[RuleCode::TLA024],
+        needs_clean_tree: true,
+        run: |ctx, tree, _, diags| pluscal::check_translation(ctx, tree, diags),
+    },
+    Check {
+        codes: &[
+            RuleCode::TLA025,
+            RuleCode::TLA026,
+            RuleCode::TLA027,
+            RuleCode::TLA028,
+        ],
+        needs_clean_tree: true,
+        run: |ctx, tree, _, diags| pluscal::check_algorithm(ctx, tree, diags),
+    },
+    Check {
+        codes: &[
+            RuleCode::TLA015,
+            RuleCode::TLA016,
+            RuleCode::TLA017,
+            RuleCode::TLA018,
+        ],
+        needs_clean_tree: true,
+        run: |ctx, tree, symbols, diags| {
+            rule_naming_conventions(ctx, tree, symbols, &ctx.options.naming, diags)
+        },
+    },
+    Check {
+        codes: &[RuleCode::TLA008],
+        needs_clean_tree: true,
+        run: levels::check_levels,
+    },
+    Check {
+        codes: &[RuleCode::TLA003],
+        needs_clean_tree: false,
+        run: rule_undefined_identifiers,
+    },
+];
+
+/// Runs the checks of the enabled rules that apply to `tree` and returns
+/// the codes they cover; on a tree with syntax errors, those that need a
+/// clean one are skipped.
+pub fn run_all_rules(
+    ctx: &FileContext,
+    tree: &Tree,
+    diags: &mut Vec<Diagnostic>,
+) -> HashSet<RuleCode> {
+    let symbols = SymbolTable::build(tree, &ctx.src, ctx.imports.names.iter().cloned());
+    let clean = !tree.root_node().has_error();
+    let mut ran = HashSet::new();
+    for check in CHECKS {
+        let enabled: Vec<RuleCode> = check
+            .codes
+            .iter()
+            .copied()
+            .filter(|code| ctx.options.rules.is_enabled(*code))
+            .collect();
+        if !enabled.is_empty() && (clean || !check.needs_clean_tree) {
+            (check.run)(ctx, tree, &symbols, diags);
+            ran.extend(enabled);
+        }
+    }
+    ran
+}
+
+/// Reports one TLA000 per broken region: error recovery tends to leave a
+/// trail of ERROR and MISSING nodes behind a single mistake, so only the
+/// outermost one is reported, and at most once per top-level unit.
+fn rule_syntax_errors(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
+    let mut reported_units = HashSet::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if !node.is_error() && !node.is_missing() {
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+            continue;
+        }
+        if !reported_units.insert(top_level_unit(node).id()) {
+            continue;
+        }
+        // Without its `====` line, the module does not parse at all and
+        // recovery wraps it whole, header included, in one ERROR.
+        if spans_header(node) && !ctx.src.lines().any(|l| l.starts_with("====")) {
+            let end = ctx.src.trim_end().len();
+            diags.push(ctx.diagnostic(
+                end..end,
+                RuleCode::TLA000,
+                "Syntax error: missing `====` at the end of the module".to_string(),
+            ));
+            continue;
+        }
+        diags.push(syntax_error(ctx, first_syntax_error(node).unwrap_or(node)));
+    }
+}
+
+/// Describes the innermost ERROR or MISSING node `node`.
+fn syntax_error(ctx: &FileContext, node: Node) -> Diagnostic {
+    if node.is_missing() {
+        let message = format!("Syntax error: missing {}", describe_kind(node));
+        return ctx.diagnostic(node.byte_range(), RuleCode::TLA000, message);
+    }
+    if let Some(diag) = unfinished_definition(ctx, node) {
+        return diag;
+    }
+    let unexpected = unexpected_token(node);
+    let text = unexpected
+        .utf8_text(ctx.src.as_bytes())
+        .unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default();
+    let mut message = format!("Syntax error: unexpected `{}`", truncate(text, 24));
+    if let Some((open, expected)) = unclosed(unexpected) {
+        let (line, col) = ctx.position(open.start_byte());
+        let verb = if open.kind().starts_with(char::is_alphabetic) {
+            "complete"
+        } else {
+            "close"
+        };
+        message.push_str(&format!(
+            "; expected `{expected}` to {verb} `{}` at {line}:{col}",
+            open.kind()
+        ));
+    }
+    ctx.diagnostic(
+        unexpected.start_byte()..node.end_byte(),
+        RuleCode::TLA000,
+        message,
+    )
+}
+
+/// The first MISSING node, or ERROR node without ERROR descendants, in
+/// `node` in source order.
+fn first_syntax_error(node: Node) -> Option<Node> {
+    if node.is_missing() || (node.is_error() && !has_error_descendant(node)) {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    children.into_iter().find_map(first_syntax_error)
+}
+
+/// The ancestor of `node` that sits directly in a module or the file.
+fn top_level_unit(node: Node) -> Node {
+    let mut unit = node;
+    while let Some(parent) = unit.parent() {
+        if matches!(parent.kind(), "module" | "source_file") {
+            break;
+        }
+        unit = parent;
+    }
+    unit
+}
+
+fn rule_duplicate_definitions(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut report = |id: SymbolId, message: String, first: Option<SymbolId>| {
+        let range = &symbols.symbol(id).name_range;
+        diags.push(Diagnostic {
+            labels: first
+                .map(|first| {
+                    let first = symbols.symbol(first);
+                    let what = match first.kind {
+                        SymbolKind::Variable | SymbolKind::Constant => "first declared here",
+                        _ => "first defined here",
+                    };
+                    ctx.label(first.name_range.clone(), what)
+                })
+                .into_iter()
+                .collect(),
+            ..ctx.diagnostic(range.clone(), RuleCode::TLA010, message)
+        });
+    };
+
+    for scope in symbols.scopes() {
+        if scope.kind == ScopeKind::Imports {
+            continue;
+        }
+
+        // A RECURSIVE declaration is meant to be followed by a definition.
+        let mut first: Vec<SymbolId> = Vec::new();
+        for &id in &scope.symbols {
+            let sym = symbols.symbol(id);
+            if sym.kind == SymbolKind::RecursiveDeclaration {
+                continue;
+            }
+            let Some(&earlier) = first.iter().find(|e| symbols.symbol(**e).name == sym.name) else {
+                first.push(id);
+                continue;
+            };
+            let prev = symbols.symbol(earlier);
+            let (line, col) = ctx.position(prev.name_range.start);
+            let message = match (prev.kind, sym.kind) {
+                (SymbolKind::Variable, SymbolKind::Variable) => {
+                    format!(
+                        "Variable `{}` is declared twice; first declared at {line}:{col}",
+                        sym.name
+                    )
                 }
+                (SymbolKind::Variable, SymbolKind::Constant)
+                | (SymbolKind::Constant, SymbolKind::Variable) => format!(
+                    "`{}` is declared both as VARIABLE and CONSTANT; first declared at {line}:{col}",
+                    sym.name
+                ),
+                _ => format!("`{}` is already defined at {line}:{col}", sym.name),
+            };
+            report(id, message, Some(earlier));
+        }
+
+        // Definitions may not reuse a name a standard module exports.
+        if scope.kind != ScopeKind::Module {
+            continue;
+        }
+        let Some(module) = node_for_range(tree.root_node(), &scope.range, "module") else {
+            continue;
+        };
+        let mut cursor = module.walk();
+        let extended: Vec<String> = module
+            .children(&mut cursor)
+            .filter(|n| n.kind() == "extends")
+            .flat_map(|ext| {
+                let mut inner = ext.walk();
+                ext.named_children(&mut inner)
+                    .map(|n| text(ctx, n).to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for id in first {
+            let name = &symbols.symbol(id).name;
+            let origin = extended.iter().find(|module| {
+                stdlib::exports(module).is_some_and(|names| names.iter().any(|(n, _)| n == name))
+            });
+            if let Some(module) = origin {
+                report(
+                    id,
+                    format!("`{name}` is already defined by the standard module {module}"),
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// SANY finds `EXTENDS Foo` by opening `Foo.tla`, and a file holds exactly
+/// one top-level module.
+fn rule_module_names(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
+    let mut report = |node: Node, message: String| {
+        diags.push(ctx.diagnostic(node.byte_range(), RuleCode::TLA013, message));
+    };
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let modules: Vec<Node> = root
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "module")
+        .collect();
+    let Some((first, rest)) = modules.split_first() else {
+        return;
+    };
+
+    let file_stem = ctx.path.file_stem().and_then(|s| s.to_str());
+    if let (Some(name), Some(stem)) = (module_name(*first), file_stem) {
+        if text(ctx, name) != stem {
+            report(
+                name,
+                format!(
+                    "Module `{}` must be in a file named `{}.tla`",
+                    text(ctx, name),
+                    text(ctx, name)
+                ),
+            );
+        }
+    }
+    for extra in rest {
+        let name = module_name(*extra).unwrap_or(*extra);
+        report(
+            name,
+            format!(
+                "File contains more than one top-level module; move `{}` to its own file",
+                text(ctx, name)
+            ),
+        );
+    }
+
+    for module in &modules {
+        check_nested_modules(ctx, *module, &mut vec![], &mut report);
+    }
+}
+
+/// Nested modules may not reuse the name of an enclosing module or of an
+/// earlier module nested in the same one.
+fn check_nested_modules<'t>(
+    ctx: &FileContext,
+    module: Node<'t>,
+    enclosing: &mut Vec<Node<'t>>,
+    report: &mut impl FnMut(Node, String),
+) {
+    let Some(name) = module_name(module) else {
+        return;
+    };
+    enclosing.push(name);
+    let mut siblings: Vec<Node> = Vec::new();
+    let mut cursor = module.walk();
+    let nested: Vec<Node> = module
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "module")
+        .collect();
+    for inner in nested {
+        let Some(inner_name) = module_name(inner) else {
+            continue;
+        };
+        let clash = enclosing
+            .iter()
+            .chain(&siblings)
+            .find(|other| text(ctx, **other) == text(ctx, inner_name));
+        if let Some(other) = clash {
+            let (line, col) = ctx.position(other.start_byte());
+            report(
+                inner_name,
+                format!(
+                    "Nested module `{}` conflicts with the module declared at {line}:{col}",
+                    text(ctx, inner_name)
+                ),
+            );
+        }
+        siblings.push(inner_name);
+        check_nested_modules(ctx, inner, enclosing, report);
+    }
+    enclosing.pop();
+}
+
+fn module_name(module: Node) -> Option<Node> {
+    module.child_by_field_name("name").or_else(|| {
+        let mut cursor = module.walk();
+        module
+            .named_children(&mut cursor)
+            .find(|n| n.kind() == "identifier")
+    })
+}
+
+fn rule_naming_conventions(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    naming: &NamingConventions,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut report = |range: &std::ops::Range<usize>, code: RuleCode, message: String| {
+        diags.push(ctx.diagnostic(range.clone(), code, message));
+    };
+
+    let translation = pluscal::translation_range(ctx, tree);
+    for (_, sym) in symbols.module_symbols() {
+        if translation
+            .as_ref()
+            .is_some_and(|range| range.contains(&sym.name_range.start))
+        {
+            continue;
+        }
+        let (label, case, code) = match sym.kind {
+            SymbolKind::Operator | SymbolKind::Function => {
+                ("Operator", naming.operators, RuleCode::TLA015)
+            }
+            SymbolKind::Variable => ("Variable", naming.variables, RuleCode::TLA016),
+            SymbolKind::Constant => ("Constant", naming.constants, RuleCode::TLA017),
+            _ => continue,
+        };
+        let customary = sym.kind == SymbolKind::Operator && sym.name == "vars";
+        if !customary && !case.matches(&sym.name) {
+            report(
+                &sym.name_range,
+                code,
+                format!("{label} `{}` should be {case}", sym.name),
+            );
+        }
+
+        let is_invariant =
+            sym.kind == SymbolKind::Operator && ctx.external.config.invariants.contains(&sym.name);
+        if is_invariant
+            && !naming.invariant_prefixes.is_empty()
+            && !naming
+                .invariant_prefixes
+                .iter()
+                .any(|prefix| sym.name.starts_with(prefix.as_str()))
+        {
+            let prefixes: Vec<String> = naming
+                .invariant_prefixes
+                .iter()
+                .map(|p| format!("`{p}`"))
+                .collect();
+            report(
+                &sym.name_range,
+                RuleCode::TLA018,
+                format!(
+                    "Invariant `{}` should start with {}",
+                    sym.name,
+                    prefixes.join(" or ")
+                ),
+            );
+        }
+    }
+}
+
+fn rule_unused_variables(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    for (id, sym) in symbols.module_symbols() {
+        // Redeclarations are reported by TLA010; references go to the first.
+        if sym.kind != SymbolKind::Variable || !binds(symbols, id) {
+            continue;
+        }
+        // Modules that EXTEND this one may use it in their own actions.
+        if symbols.references_to(id).next().is_none() && !ctx.external.modules.contains(&sym.name) {
+            diags.push(Diagnostic {
+                fix: remove_variable(ctx, tree, &sym.name_range, &sym.name),
+                ..ctx.diagnostic(
+                    sym.name_range.clone(),
+                    RuleCode::TLA001,
+                    format!("Variable `{}` is declared but never used", sym.name),
+                )
+            });
+        }
+    }
+}
+
+/// Removes a name from its VARIABLES list, or the whole declaration when
+/// it is the only name.
+fn remove_variable(
+    ctx: &FileContext,
+    tree: &Tree,
+    name_range: &Range<usize>,
+    name: &str,
+) -> Option<Fix> {
+    let ident = node_for_range(tree.root_node(), name_range, "identifier")?;
+    let decl = ident
+        .parent()
+        .filter(|p| p.kind() == "variable_declaration")?;
+    let mut cursor = decl.walk();
+    let names: Vec<Node> = decl
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "identifier")
+        .collect();
+    let idx = names.iter().position(|n| *n == ident)?;
+    let edit = if names.len() == 1 {
+        let (start, end) = whole_lines(ctx, decl.start_byte(), decl.end_byte());
+        Edit::delete(start, end)
+    } else if let Some(next) = names.get(idx + 1) {
+        Edit::delete(ident.start_byte(), next.start_byte())
+    } else {
+        Edit::delete(names[idx - 1].end_byte(), ident.end_byte())
+    };
+    Some(Fix {
+        description: format!("Remove `{name}` from the variable declaration"),
+        edits: vec![edit],
+    })
+}
+
+/// Widens `start..end` to its whole lines, with the line break, when
+/// nothing else shares them.
+fn whole_lines(ctx: &FileContext, start: usize, end: usize) -> (usize, usize) {
+    let line_start = ctx.src[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = ctx.src[end..]
+        .find('\n')
+        .map_or(ctx.src.len(), |i| end + i + 1);
+    if ctx.src[line_start..start].trim().is_empty() && ctx.src[end..line_end].trim().is_empty() {
+        (line_start, line_end)
+    } else {
+        (start, end)
+    }
+}
+
+fn rule_unused_constants(ctx: &FileContext, symbols: &SymbolTable, diags: &mut Vec<Diagnostic>) {
+    for (id, sym) in symbols.module_symbols() {
+        if sym.kind != SymbolKind::Constant || !binds(symbols, id) {
+            continue;
+        }
+        // References include ASSUME bodies; a model that assigns the
+        // constant, or a module extending this one, also counts as a use.
+        let used = symbols.references_to(id).next().is_some()
+            || ctx.external.config.constants.contains(&sym.name)
+            || ctx.external.modules.contains(&sym.name);
+        if used {
+            continue;
+        }
+
+        diags.push(ctx.diagnostic(
+            sym.name_range.clone(),
+            RuleCode::TLA005,
+            format!("Constant `{}` is declared but never used", sym.name),
+        ));
+    }
+}
+
+fn rule_missing_init_next(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let has_variables = symbols
+        .module_symbols()
+        .any(|(_, s)| s.kind == SymbolKind::Variable);
+    let init = actions::find_operator(tree, symbols, "Init");
+    let has_init = init.is_some();
+    let has_next = actions::find_operator(tree, symbols, "Next").is_some();
+
+    if !has_variables {
+        return;
+    }
+
+    // Skeletons go right after the variable declarations, or after Init for
+    // Next, so that they precede any use.
+    let variables: Vec<&str> = symbols
+        .module_symbols()
+        .filter(|(id, s)| s.kind == SymbolKind::Variable && binds(symbols, *id))
+        .map(|(_, s)| s.name.as_str())
+        .collect();
+    let declarations_end = symbols
+        .module_symbols()
+        .filter(|(_, s)| s.kind == SymbolKind::Variable)
+        .filter_map(|(_, s)| node_for_range(tree.root_node(), &s.name_range, "identifier"))
+        .filter_map(|ident| ident.parent())
+        .map(|decl| decl.end_byte())
+        .max();
+    let skeleton = |at: Option<usize>, name: &str, body: String| {
+        at.map(|at| Fix {
+            description: format!("Insert a skeleton `{name}`"),
+            edits: vec![Edit::insert(at, format!("\n\n{name} ==\n{body}"))],
+        })
+    };
+
+    // Reported at the module name, pointing at the first declaration.
+    let module_name = tree
+        .root_node()
+        .named_child(0)
+        .and_then(module_name)
+        .map_or(0..0, |name| name.byte_range());
+    let first_declaration = symbols
+        .module_symbols()
+        .filter(|(_, s)| s.kind == SymbolKind::Variable)
+        .filter_map(|(_, s)| node_for_range(tree.root_node(), &s.name_range, "identifier"))
+        .filter_map(|ident| ident.parent())
+        .min_by_key(|decl| decl.start_byte())
+        .map(|decl| ctx.label(decl.byte_range(), "variables declared here"));
+    let missing = |name: &str, help: &str, fix: Option<Fix>| Diagnostic {
+        labels: first_declaration.iter().cloned().collect(),
+        help: Some(help.to_string()),
+        fix,
+        ..ctx.diagnostic(
+            module_name.clone(),
+            RuleCode::TLA002,
+            format!("Module declares VARIABLES but is missing {name} operator"),
+        )
+    };
+
+    if !has_init {
+        diags.push(missing(
+            "Init",
+            "define `Init` as the predicate every initial state satisfies",
+            skeleton(
+                declarations_end,
+                "Init",
+                variables
+                    .iter()
+                    .map(|v| format!("    /\\ {v} = 0"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        ));
+    }
+    if !has_next {
+        diags.push(missing(
+            "Next",
+            "define `Next` as the disjunction of the actions a step may take",
+            skeleton(
+                init.map(|(_, def)| def.end_byte()).or(declarations_end),
+                "Next",
+                format!("    UNCHANGED {}", tuple(&variables)),
+            ),
+        ));
+    } else {
+        rule_unchanged_variables(ctx, tree, symbols, diags);
+    }
+}
+
+/// `x` for one name, `<<x, y>>` otherwise.
+fn tuple(names: &[&str]) -> String {
+    match names {
+        [name] => name.to_string(),
+        _ => format!("<<{}>>", names.join(", ")),
+    }
+}
+
+/// TLC only notices an action that leaves a variable unspecified when it
+/// evaluates that action, which may be deep into a run.
+fn rule_unchanged_variables(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Variables nobody references are already reported by TLA001.
+    let variables: Vec<(SymbolId, &str)> = symbols
+        .module_symbols()
+        .filter(|(id, s)| {
+            s.kind == SymbolKind::Variable && symbols.references_to(*id).next().is_some()
+        })
+        .map(|(id, s)| (id, s.name.as_str()))
+        .collect();
+
+    // Several leaves can belong to one operator (IF branches, nested
+    // disjunctions); report each operator once, listing every miss. Each
+    // leaf keeps its own misses for the fix.
+    type Leaf<'t, 's> = (Node<'t>, Vec<(SymbolId, &'s str)>);
+    let mut missing: BTreeMap<usize, (Option<SymbolId>, usize, Vec<Leaf>)> = BTreeMap::new();
+    for action in actions::next_actions(tree, symbols) {
+        // A `FALSE` branch, such as `[] OTHER -> FALSE`, never takes a step.
+        if action.node.kind() == "boolean" && text(ctx, action.node) == "FALSE" {
+            continue;
+        }
+        let Some(covered) = actions::covered_variables(tree, symbols, action.node) else {
+            continue;
+        };
+        let unassigned: Vec<(SymbolId, &str)> = variables
+            .iter()
+            .filter(|(id, _)| !covered.contains(id))
+            .copied()
+            .collect();
+        if unassigned.is_empty() {
+            continue;
+        }
+
+        let (start, end) = match action.operator {
+            Some(id) => {
+                let range = &symbols.symbol(id).name_range;
+                (range.start, range.end)
+            }
+            None => (action.node.start_byte(), action.node.end_byte()),
+        };
+        let entry = missing
+            .entry(start)
+            .or_insert_with(|| (action.operator, end, Vec::new()));
+        // An operator reached through several disjuncts is one leaf.
+        if !entry.2.iter().any(|(leaf, _)| *leaf == action.node) {
+            entry.2.push((action.node, unassigned));
+        }
+    }
+
+    for (start, (operator, end, leaves)) in missing {
+        let mut names: Vec<&str> = Vec::new();
+        for (_, name) in leaves.iter().flat_map(|(_, missing)| missing) {
+            if !names.contains(name) {
+                names.push(name);
+            }
+        }
+        let subject = match operator {
+            Some(id) => format!("Action `{}`", symbols.symbol(id).name),
+            None => "Disjunct of `Next`".to_string(),
+        };
+        let list = names
+            .iter()
+            .map(|n| format!("`{n}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        diags.push(Diagnostic {
+            fix: leaves
+                .iter()
+                .map(|(leaf, missing)| leave_unchanged(ctx, tree, symbols, *leaf, missing))
+                .collect::<Option<Vec<Edit>>>()
+                .map(|edits| Fix {
+                    description: format!("Leave {list} UNCHANGED"),
+                    edits,
+                }),
+            ..ctx.diagnostic(
+                start..end,
+                RuleCode::TLA006,
+                format!("{subject} neither assigns nor leaves UNCHANGED: {list}"),
+            )
+        });
+    }
+}
+
+/// Adds the `missing` variables to an UNCHANGED conjunct of `leaf`, or adds
+/// one. There is no fix when a branch inside the leaf assigns one of them,
+/// since leaving it UNCHANGED as well would disable that branch.
+fn leave_unchanged(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    leaf: Node,
+    missing: &[(SymbolId, &str)],
+) -> Option<Edit> {
+    let touched = actions::touched_variables(tree, symbols, leaf);
+    if missing.iter().any(|(id, _)| touched.contains(id)) {
+        return None;
+    }
+    let names: Vec<&str> = missing.iter().map(|(_, name)| *name).collect();
+
+    for conjunct in conjuncts(leaf) {
+        if conjunct.kind() != "bound_prefix_op" || actions::op_symbol(conjunct) != Some("unchanged")
+        {
+            continue;
+        }
+        let operand = conjunct.child_by_field_name("rhs")?;
+        match operand.kind() {
+            "tuple_literal" => {
+                let close = operand.child(operand.child_count().checked_sub(1)?)?;
+                let separator = if operand.named_child_count() > 2 {
+                    ", "
+                } else {
+                    ""
+                };
+                return Some(Edit::insert(
+                    close.start_byte(),
+                    format!("{separator}{}", names.join(", ")),
+                ));
+            }
+            "identifier_ref"
+                if symbols
+                    .resolve(operand)
+                    .is_some_and(|id| symbols.symbol(id).kind == SymbolKind::Variable) =>
+            {
+                return Some(Edit {
+                    start: operand.start_byte(),
+                    end: operand.end_byte(),
+                    replacement: format!("<<{}, {}>>", text(ctx, operand), names.join(", ")),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let unchanged = format!("UNCHANGED {}", tuple(&names));
+    if leaf.kind() == "conj_list" {
+        let last = leaf.named_child(leaf.named_child_count().checked_sub(1)?)?;
+        let (_, col) = ctx.position(last.start_byte());
+        return Some(Edit::insert(
+            last.end_byte(),
+            format!("\n{}/\\ {unchanged}", " ".repeat(col - 1)),
+        ));
+    }
+    // Expressions that extend as far right as possible, or bind looser
+    // than `/\`, need parentheses.
+    let open_ended = matches!(
+        leaf.kind(),
+        "bounded_quantification" | "unbounded_quantification" | "choose"
+    ) || (leaf.kind() == "bound_infix_op"
+        && matches!(
+            actions::op_symbol(leaf),
+            Some("implies" | "equiv" | "iff" | "leads_to" | "plus_arrow")
+        ));
+    if open_ended {
+        return Some(Edit {
+            start: leaf.start_byte(),
+            end: leaf.end_byte(),
+            replacement: format!("({}) /\\ {unchanged}", text(ctx, leaf)),
+        });
+    }
+    Some(Edit::insert(leaf.end_byte(), format!(" /\\ {unchanged}")))
+}
+
+/// The operands of a conjunction, whether a `/\` list or infix `/\`.
+fn conjuncts(node: Node) -> Vec<Node> {
+    match node.kind() {
+        "conj_list" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .filter_map(|item| item.named_child(1))
+                .flat_map(conjuncts)
+                .collect()
+        }
+        "bound_infix_op" if actions::op_symbol(node) == Some("land") => ["lhs", "rhs"]
+            .iter()
+            .filter_map(|f| node.child_by_field_name(f))
+            .flat_map(conjuncts)
+            .collect(),
+        _ => vec![node],
+    }
+}
+
+/// Checks that each temporal specification has the canonical
+/// `Init /\ [][Next]_vars` shape and that its subscripts cover every variable.
+fn rule_spec_shape(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let variables: Vec<(SymbolId, &str)> = symbols
+        .module_symbols()
+        .filter(|(_, s)| s.kind == SymbolKind::Variable)
+        .map(|(id, s)| (id, s.name.as_str()))
+        .collect();
+    if variables.is_empty() {
+        return;
+    }
+
+    let mut report = |start: usize, end: usize, message: String| {
+        diags.push(ctx.diagnostic(start..end, RuleCode::TLA009, message));
+    };
+
+    let mut names: Vec<&str> = ctx
+        .external
+        .config
+        .specifications
+        .iter()
+        .map(String::as_str)
+        .collect();
+    names.sort();
+    if !names.contains(&"Spec") {
+        names.insert(0, "Spec");
+    }
+
+    for name in names {
+        let Some((spec, _)) = actions::find_operator(tree, symbols, name) else {
+            continue;
+        };
+        let spec_name = &symbols.symbol(spec).name_range;
+
+        for entry in ["Init", "Next"] {
+            let Some((target, _)) = actions::find_operator(tree, symbols, entry) else {
+                continue;
+            };
+            let referenced = !actions::reachable_matching(
+                tree,
+                symbols,
+                spec,
+                |node| node.kind() == "identifier_ref" && symbols.resolve(node) == Some(target),
+                |_| false,
+            )
+            .is_empty();
+            if !referenced {
+                report(
+                    spec_name.start,
+                    spec_name.end,
+                    format!("`{name}` does not reference `{entry}`"),
+                );
+            }
+        }
+
+        let parts = actions::reachable_matching(
+            tree,
+            symbols,
+            spec,
+            |node| matches!(node.kind(), "step_expr_or_stutter" | "fairness"),
+            |_| false,
+        );
+        let Some(step) = parts.iter().find(|n| n.kind() == "step_expr_or_stutter") else {
+            report(
+                spec_name.start,
+                spec_name.end,
+                format!("`{name}` has no `[][Next]_vars` conjunct"),
+            );
+            continue;
+        };
+        let Some(vars) = step_subscript(*step) else {
+            continue;
+        };
+        let covered = actions::state_tuple(tree, symbols, vars, &mut Vec::new());
+        let missing: Vec<String> = variables
+            .iter()
+            .filter(|(id, _)| !covered.contains(id))
+            .map(|(_, name)| format!("`{name}`"))
+            .collect();
+        if !missing.is_empty() {
+            report(
+                vars.start_byte(),
+                vars.end_byte(),
+                format!(
+                    "Subscript `{}` omits {}; steps that change only those variables count as stuttering",
+                    text(ctx, vars),
+                    missing.join(", ")
+                ),
+            );
+        }
+
+        for fairness in parts.iter().filter(|n| n.kind() == "fairness") {
+            let Some(subscript) = step_subscript(*fairness) else {
+                continue;
+            };
+            if actions::state_tuple(tree, symbols, subscript, &mut Vec::new()) != covered {
+                report(
+                    subscript.start_byte(),
+                    subscript.end_byte(),
+                    format!(
+                        "Fairness subscript `{}` differs from the `[][Next]_{}` subscript",
+                        text(ctx, subscript),
+                        text(ctx, vars)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn rule_unused_operators(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Modules without state are libraries whose operators are meant for
+    // importers we may not be linting.
+    let is_spec = symbols
+        .module_symbols()
+        .any(|(_, s)| s.kind == SymbolKind::Variable);
+    if !is_spec {
+        return;
+    }
+    // The PlusCal translator defines `Termination` and friends whether or
+    // not the spec uses them.
+    let translation = pluscal::translation_range(ctx, tree);
+
+    for (id, sym) in symbols.module_symbols() {
+        if !matches!(sym.kind, SymbolKind::Operator | SymbolKind::Function)
+            || ENTRY_POINTS.contains(&sym.name.as_str())
+            || !binds(symbols, id)
+            || translation
+                .as_ref()
+                .is_some_and(|range| range.contains(&sym.name_range.start))
+        {
+            continue;
+        }
+        // A recursive call inside the definition does not make it used; a
+        // use before the definition is reported by TLA003 instead.
+        let used = symbols
+            .references_to(id)
+            .any(|r| !sym.definition_range.contains(&r.range.start))
+            || symbols
+                .unresolved()
+                .any(|r| symbols.lookup(r.scope, &r.name) == Some(id))
+            || ctx.external.modules.contains(&sym.name)
+            || ctx.external.config.operators.contains(&sym.name);
+        if used {
+            continue;
+        }
+
+        diags.push(ctx.diagnostic(
+            sym.name_range.clone(),
+            RuleCode::TLA004,
+            format!("Operator `{}` is defined but never used", sym.name),
+        ));
+    }
+}
+
+fn rule_unused_imports(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // As for operators: a library may EXTEND modules only to re-export them.
+    let is_spec = symbols
+        .module_symbols()
+        .any(|(_, s)| s.kind == SymbolKind::Variable);
+    if !is_spec {
+        return;
+    }
+
+    let mut report = |range: Range<usize>, code: RuleCode, message: String| {
+        diags.push(ctx.diagnostic(range.clone(), code, message));
+    };
+
+    for (id, sym) in symbols.module_symbols() {
+        if sym.kind != SymbolKind::ModuleInstance || !binds(symbols, id) {
+            continue;
+        }
+        let used = symbols.references_to(id).next().is_some()
+            || ctx.external.modules.contains(&sym.name)
+            || ctx.external.config.operators.contains(&sym.name);
+        if used {
+            continue;
+        }
+        report(
+            sym.name_range.clone(),
+            RuleCode::TLA023,
+            format!("Instance `{}` is defined but never used", sym.name),
+        );
+    }
+
+    if ctx.imports.extended.is_empty() {
+        return;
+    }
+    let mut names: HashSet<&str> = symbols
+        .symbol_ids()
+        .filter(|id| {
+            let sym = symbols.symbol(*id);
+            sym.kind == SymbolKind::Imported && symbols.references_to(*id).next().is_some()
+        })
+        .map(|id| symbols.symbol(id).name.as_str())
+        .collect();
+    names.extend(ctx.external.modules.iter().map(String::as_str));
+    names.extend(ctx.external.config.operators.iter().map(String::as_str));
+    let mut operators: HashSet<&str> = HashSet::new();
+    traverse(tree.root_node(), &mut |node| match node.kind() {
+        // Built into the grammar rather than parsed as identifiers.
+        "nat_number_set" => {
+            names.insert("Nat");
+        }
+        "int_number_set" => {
+            names.insert("Int");
+        }
+        "real_number_set" => {
+            names.insert("Real");
+        }
+        "bound_infix_op" | "bound_prefix_op" | "bound_postfix_op" => {
+            if let Some(symbol) = node.child_by_field_name("symbol") {
+                operators.insert(symbol.kind());
+            }
+        }
+        _ => {}
+    });
+
+    let mut cursor = tree.walk();
+    let extends: Vec<Node> = tree
+        .root_node()
+        .children(&mut cursor)
+        .filter(|n| n.kind() == "module")
+        .flat_map(|module| {
+            let mut cursor = module.walk();
+            module
+                .children(&mut cursor)
+                .filter(|n| n.kind() == "extends")
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for node in extends {
+        let mut cursor = node.walk();
+        for module in node.named_children(&mut cursor) {
+            let name = text(ctx, module);
+            let Some(exports) = ctx.imports.extended.get(name) else {
+                continue;
+            };
+            let used = exports.names.iter().any(|n| names.contains(n.as_str()))
+                || exports
+                    .symbols
+                    .iter()
+                    .any(|s| operators.contains(s.as_str()));
+            if !used {
+                report(
+                    module.byte_range(),
+                    RuleCode::TLA022,
+                    format!("Module `{name}` is extended but none of its definitions are used"),
+                );
+            }
+        }
+    }
+}
+
+fn rule_prime_misuse(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut report = |node: Node, message: String| {
+        diags.push(ctx.diagnostic(node.byte_range(), RuleCode::TLA007, message));
+    };
+
+    traverse(tree.root_node(), &mut |node| {
+        if !actions::is_prime(node) {
+            return;
+        }
+        let Some(mut operand) = node.child_by_field_name("lhs") else {
+            return;
+        };
+        while operand.kind() == "parentheses" {
+            match operand.named_child(0) {
+                Some(inner) => operand = inner,
+                None => return,
             }
         }
+
+        let mut primed_twice = false;
+        traverse(operand, &mut |inner| {
+            primed_twice |= actions::is_prime(inner)
+        });
+        if primed_twice {
+            report(node, format!("`{}` is primed twice", text(ctx, node)));
+            return;
+        }
+        if operand.kind() != "identifier_ref" {
+            return;
+        }
+        let what = match symbols.resolve(operand).map(|id| symbols.symbol(id).kind) {
+            Some(SymbolKind::Constant) => "constant",
+            Some(SymbolKind::BoundVariable) => "bound variable",
+            _ => return,
+        };
+        report(
+            node,
+            format!(
+                "Cannot prime {what} `{}`; only state variables have a next-state value",
+                text(ctx, operand)
+            ),
+        );
     });
 
-    let mut used: HashSet<String> = HashSet::new();
-    traverse(root, &mut |node| {
-        if is_identifier_use(node) {
-            if let Some(parent) = node.parent() {
-                if is_decl_node(parent) {
+    // Init and invariants are state predicates: they are evaluated on a
+    // single state, so there is no next state for a prime to refer to.
+    let mut predicates: Vec<(SymbolId, String)> = Vec::new();
+    if let Some((id, _)) = actions::find_operator(tree, symbols, "Init") {
+        predicates.push((id, "`Init`".to_string()));
+    }
+    let mut invariants: Vec<&String> = ctx.external.config.invariants.iter().collect();
+    invariants.sort();
+    for name in invariants {
+        if let Some((id, _)) = actions::find_operator(tree, symbols, name) {
+            predicates.push((id, format!("invariant `{name}`")));
+        }
+    }
+    for (id, label) in predicates {
+        for prime in actions::primes_reachable_from(tree, symbols, id) {
+            report(
+                prime,
+                format!(
+                    "`{}` is primed inside {label}, which must be a state predicate",
+                    text(ctx, prime)
+                ),
+            );
+        }
+    }
+}
+
+/// Whether references to `id`'s name in its own scope resolve to it.
+fn binds(symbols: &SymbolTable, id: SymbolId) -> bool {
+    let sym = symbols.symbol(id);
+    symbols.lookup(sym.scope, &sym.name) == Some(id)
+}
+
+/// The `v` of `[A]_v` or `WF_v(A)`.
+fn step_subscript(node: Node) -> Option<Node> {
+    if node.kind() == "step_expr_or_stutter" {
+        node.named_child(node.named_child_count().checked_sub(1)?)
+    } else {
+        node.named_child(0)
+    }
+}
+
+fn text<'s>(ctx: &'s FileContext, node: Node) -> &'s str {
+    node.utf8_text(ctx.src.as_bytes()).unwrap_or_default()
+}
+
+fn rule_operator_arity(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut report = |node: Node, message: String, definition: Option<SymbolId>| {
+        let labels = definition
+            .map(|id| symbols.symbol(id))
+            .filter(|sym| sym.kind != SymbolKind::Imported)
+            .map(|sym| ctx.label(sym.name_range.clone(), "defined here"));
+        diags.push(Diagnostic {
+            labels: labels.into_iter().collect(),
+            ..ctx.diagnostic(node.byte_range(), RuleCode::TLA011, message)
+        });
+    };
+
+    traverse(tree.root_node(), &mut |node| match node.kind() {
+        "bound_op" => {
+            let Some(name) = node.child_by_field_name("name") else {
+                return;
+            };
+            let Some(callee) = symbols.resolve(name) else {
+                return;
+            };
+            let mut cursor = node.walk();
+            let args: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter(|c| *c != name)
+                .collect();
+            if let Some(expected) = arity(ctx, symbols, callee) {
+                if args.len() != expected {
+                    report(
+                        node,
+                        format!(
+                            "`{}` expects {}, got {}",
+                            text(ctx, name),
+                            count(expected, "argument"),
+                            args.len()
+                        ),
+                        Some(callee),
+                    );
                     return;
                 }
             }
-            if let Ok(text) = node.utf8_text(ctx.src.as_bytes()) {
-                used.insert(text.to_string());
+
+            // Operators passed as arguments must match the parameter they fill.
+            let params = operator_parameters(tree, symbols, callee);
+            for (arg, param) in args.iter().zip(params) {
+                let expected = if param.kind() == "operator_declaration" {
+                    declared_arity(param)
+                } else {
+                    0
+                };
+                let given = match arg.kind() {
+                    "lambda" => {
+                        let mut cursor = arg.walk();
+                        arg.named_children(&mut cursor)
+                            .filter(|c| c.kind() == "identifier")
+                            .count()
+                    }
+                    "identifier_ref" => {
+                        match symbols.resolve(*arg).and_then(|id| arity(ctx, symbols, id)) {
+                            Some(given) => given,
+                            None => continue,
+                        }
+                    }
+                    _ => 0,
+                };
+                if given != expected {
+                    let param_name = param.child_by_field_name("name").unwrap_or(param);
+                    report(
+                        *arg,
+                        format!(
+                            "Parameter `{}` of `{}` expects an operator taking {}, got `{}` taking {}",
+                            text(ctx, param_name),
+                            text(ctx, name),
+                            count(expected, "argument"),
+                            text(ctx, *arg),
+                            count(given, "argument")
+                        ),
+                        Some(callee),
+                    );
+                }
+            }
+        }
+        "identifier_ref" => {
+            // Arguments are checked against the parameter they fill above;
+            // substitutions may pass operators to operator constants.
+            if node.parent().is_some_and(|p| {
+                matches!(p.kind(), "bound_op" | "substitution" | "subexpr_component")
+            }) {
+                return;
+            }
+            let Some(id) = symbols.resolve(node) else {
+                return;
+            };
+            if let Some(expected @ 1..) = arity(ctx, symbols, id) {
+                report(
+                    node,
+                    format!(
+                        "`{}` expects {} but is used without any",
+                        text(ctx, node),
+                        count(expected, "argument")
+                    ),
+                    Some(id),
+                );
             }
         }
+        _ => {}
     });
+}
+
+/// The number of arguments an operator-like symbol takes, when known.
+fn arity(ctx: &FileContext, symbols: &SymbolTable, id: SymbolId) -> Option<usize> {
+    let sym = symbols.symbol(id);
+    match sym.kind {
+        SymbolKind::Operator
+        | SymbolKind::Parameter
+        | SymbolKind::Constant
+        | SymbolKind::RecursiveDeclaration => Some(sym.arity),
+        SymbolKind::Imported => ctx.imports.arities.get(&sym.name).copied(),
+        _ => None,
+    }
+}
+
+/// The parameter nodes of a user-defined operator.
+fn operator_parameters<'t>(tree: &'t Tree, symbols: &SymbolTable, id: SymbolId) -> Vec<Node<'t>> {
+    let sym = symbols.symbol(id);
+    if sym.kind != SymbolKind::Operator {
+        return Vec::new();
+    }
+    node_for_range(
+        tree.root_node(),
+        &sym.definition_range,
+        "operator_definition",
+    )
+    .map(parameters)
+    .unwrap_or_default()
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+fn rule_recursive_declarations(
+    ctx: &FileContext,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let mut report = |id: SymbolId, message: String| {
+        let range = &symbols.symbol(id).name_range;
+        diags.push(ctx.diagnostic(range.clone(), RuleCode::TLA012, message));
+    };
+
+    let declaration = |id: SymbolId| {
+        let sym = symbols.symbol(id);
+        symbols.scope(sym.scope).symbols.iter().copied().find(|d| {
+            let decl = symbols.symbol(*d);
+            decl.kind == SymbolKind::RecursiveDeclaration && decl.name == sym.name
+        })
+    };
+
+    for scope in symbols.scopes() {
+        for &id in &scope.symbols {
+            let decl = symbols.symbol(id);
+            if decl.kind != SymbolKind::RecursiveDeclaration {
+                continue;
+            }
+            let definition = scope.symbols.iter().copied().find(|d| {
+                let def = symbols.symbol(*d);
+                def.kind == SymbolKind::Operator && def.name == decl.name
+            });
+            match definition {
+                None => report(
+                    id,
+                    format!("RECURSIVE `{}` has no matching definition", decl.name),
+                ),
+                Some(def) if symbols.symbol(def).arity != decl.arity => report(
+                    id,
+                    format!(
+                        "RECURSIVE `{}` declares {} but the definition takes {}",
+                        decl.name,
+                        count(decl.arity, "argument"),
+                        symbols.symbol(def).arity
+                    ),
+                ),
+                Some(_) => {}
+            }
+        }
+    }
+
+    let graph = call_graph(symbols);
+    let mut operators: Vec<SymbolId> = graph.keys().copied().collect();
+    operators.sort();
+    for id in operators {
+        if declaration(id).is_some() {
+            continue;
+        }
+        let sym = symbols.symbol(id);
+        let callees = &graph[&id];
+        if callees.contains(&id) {
+            report(
+                id,
+                format!("`{}` calls itself but is not declared RECURSIVE", sym.name),
+            );
+            continue;
+        }
+        let partner = callees
+            .iter()
+            .copied()
+            .find(|callee| reaches(&graph, *callee, id));
+        if let Some(partner) = partner {
+            report(
+                id,
+                format!(
+                    "`{}` is mutually recursive with `{}` but is not declared RECURSIVE",
+                    sym.name,
+                    symbols.symbol(partner).name
+                ),
+            );
+        }
+    }
+}
 
-    for (name, byte_offset) in declared {
-        if !used.contains(&name) {
-            let (line, col) = ctx.position(byte_offset);
+/// Which operators each operator's definition refers to.
+fn call_graph(symbols: &SymbolTable) -> HashMap<SymbolId, Vec<SymbolId>> {
+    let mut graph: HashMap<SymbolId, Vec<SymbolId>> = HashMap::new();
+    for id in symbols.symbol_ids() {
+        let sym = symbols.symbol(id);
+        if sym.kind != SymbolKind::Operator {
+            continue;
+        }
+        let mut callees: Vec<SymbolId> = symbols
+            .references()
+            .iter()
+            .filter(|r| sym.definition_range.contains(&r.range.start))
+            .filter_map(|r| r.symbol)
+            .filter(|callee| symbols.symbol(*callee).kind == SymbolKind::Operator)
+            .collect();
+        callees.sort();
+        callees.dedup();
+        graph.insert(id, callees);
+    }
+    graph
+}
+
+fn reaches(graph: &HashMap<SymbolId, Vec<SymbolId>>, from: SymbolId, to: SymbolId) -> bool {
+    let mut seen = vec![from];
+    let mut stack = vec![from];
+    while let Some(current) = stack.pop() {
+        for &next in graph.get(&current).into_iter().flatten() {
+            if next == to {
+                return true;
+            }
+            if !seen.contains(&next) {
+                seen.push(next);
+                stack.push(next);
+            }
+        }
+    }
+    false
+}
+
+fn rule_shadowing(ctx: &FileContext, symbols: &SymbolTable, diags: &mut Vec<Diagnostic>) {
+    for scope in symbols.scopes() {
+        if matches!(scope.kind, ScopeKind::Imports | ScopeKind::Module) {
+            continue;
+        }
+        let Some(parent) = scope.parent else {
+            continue;
+        };
+        for &id in &scope.symbols {
+            let sym = symbols.symbol(id);
+            if sym.kind == SymbolKind::RecursiveDeclaration {
+                continue;
+            }
+            let Some(outer) = symbols.lookup(parent, &sym.name) else {
+                continue;
+            };
+            let outer = symbols.symbol(outer);
+            let label = (outer.kind != SymbolKind::Imported).then(|| {
+                ctx.label(
+                    outer.name_range.clone(),
+                    format!("shadowed {} declared here", describe_symbol(outer.kind)),
+                )
+            });
+            let original = if outer.kind == SymbolKind::Imported {
+                format!("`{}` imported through EXTENDS or INSTANCE", outer.name)
+            } else {
+                let (line, col) = ctx.position(outer.name_range.start);
+                format!(
+                    "{} `{}` at {line}:{col}",
+                    describe_symbol(outer.kind),
+                    outer.name
+                )
+            };
+
+            let shadowing = if scope.kind == ScopeKind::Let {
+                format!("LET {}", describe_symbol(sym.kind))
+            } else {
+                capitalize(describe_symbol(sym.kind))
+            };
             diags.push(Diagnostic {
-                file: ctx.path.to_string_lossy().into_owned(),
-                line,
-                column: col,
-                severity: Severity::Warning,
-                code: RuleCode::TLA001,
-                message: format!("Variable `{}` is declared but never used", name),
+                labels: label.into_iter().collect(),
+                ..ctx.diagnostic(
+                    sym.name_range.clone(),
+                    RuleCode::TLA014,
+                    format!("{shadowing} `{}` shadows {original}", sym.name),
+                )
             });
         }
     }
 }
 
-fn rule_missing_init_next(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
-    let root = tree.root_node();
-    let mut has_variables = false;
-    let mut has_init = false;
-    let mut has_next = false;
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
 
-    traverse(root, &mut |node| {
-        if is_decl_node(node) {
-            has_variables = true;
+fn describe_symbol(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Imported => "imported name",
+        SymbolKind::Variable => "variable",
+        SymbolKind::Constant => "constant",
+        SymbolKind::Operator | SymbolKind::Function => "definition",
+        SymbolKind::ModuleInstance => "module instance",
+        SymbolKind::RecursiveDeclaration => "RECURSIVE declaration",
+        SymbolKind::Theorem => "theorem",
+        SymbolKind::Assumption => "assumption",
+        SymbolKind::Parameter => "parameter",
+        SymbolKind::BoundVariable => "bound variable",
+    }
+}
+
+fn rule_case_arms(ctx: &FileContext, tree: &Tree, diags: &mut Vec<Diagnostic>) {
+    let warning = |node: Node, code: RuleCode, message: String| {
+        ctx.diagnostic(node.byte_range(), code, message)
+    };
+
+    traverse(tree.root_node(), &mut |node| {
+        if node.kind() != "case" {
+            return;
         }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
 
-        if node.kind() == "operator_definition" {
-            if let Some(name_node) = node
-                .children(&mut node.walk())
-                .find(|n| n.kind() == "identifier")
-            {
-                if let Ok(text) = name_node.utf8_text(ctx.src.as_bytes()) {
-                    match text {
-                        "Init" => has_init = true,
-                        "Next" => has_next = true,
-                        _ => {}
-                    }
-                }
+        if !children.iter().any(|c| c.kind() == "other_arm") {
+            let keyword = children.first().copied().unwrap_or(node);
+            diags.push(Diagnostic {
+                fix: other_arm(ctx, &children),
+                ..warning(
+                    keyword,
+                    RuleCode::TLA019,
+                    "CASE has no OTHER arm; TLC fails at runtime on states where no guard holds"
+                        .to_string(),
+                )
+            });
+        }
+
+        let mut seen: Vec<(Node, Vec<&str>)> = Vec::new();
+        for arm in children.iter().filter(|c| c.kind() == "case_arm") {
+            let Some(guard) = arm.named_child(0) else {
+                continue;
+            };
+            let tokens = tokens(ctx, guard);
+            if let Some((first, _)) = seen.iter().find(|(_, t)| *t == tokens) {
+                let (line, col) = ctx.position(first.start_byte());
+                diags.push(Diagnostic {
+                    labels: vec![ctx.label(first.byte_range(), "first guard")],
+                    ..warning(
+                        guard,
+                        RuleCode::TLA020,
+                        format!(
+                            "CASE guard `{}` duplicates the guard at {line}:{col}; TLC never selects this arm",
+                            truncate(text(ctx, guard), 40)
+                        ),
+                    )
+                });
+            } else {
+                seen.push((guard, tokens));
             }
         }
     });
+}
 
-    if !has_variables {
+/// Adds `[] OTHER -> FALSE` to a CASE whose arms are all predicates or
+/// actions, where it makes the CASE false instead of an error. Other
+/// CASEs have no value that is right in general.
+fn other_arm(ctx: &FileContext, children: &[Node]) -> Option<Fix> {
+    let arms: Vec<Node> = children
+        .iter()
+        .copied()
+        .filter(|c| c.kind() == "case_arm")
+        .collect();
+    let is_predicate = |arm: &Node| {
+        arm.named_child(arm.named_child_count().checked_sub(1)?)
+            .map(is_boolean)
+    };
+    if !arms.iter().all(|arm| is_predicate(arm) == Some(true)) {
+        return None;
+    }
+    let last = arms.last()?;
+    // One arm per line: align with the `[]` above.
+    let case_line = ctx.position(children.first()?.start_byte()).0;
+    let separator = match children.iter().rev().find(|c| c.kind() == "case_box") {
+        Some(the_box) if ctx.position(the_box.start_byte()).0 != case_line => {
+            let (_, col) = ctx.position(the_box.start_byte());
+            format!("\n{}", " ".repeat(col - 1))
+        }
+        _ => " ".to_string(),
+    };
+    Some(Fix {
+        description: "Add `[] OTHER -> FALSE`".to_string(),
+        edits: vec![Edit::insert(
+            last.end_byte(),
+            format!("{separator}[] OTHER -> FALSE"),
+        )],
+    })
+}
+
+/// Whether `node` is syntactically a Boolean: a literal, a comparison, a
+/// logical connective or a quantifier.
+fn is_boolean(node: Node) -> bool {
+    match node.kind() {
+        "boolean"
+        | "conj_list"
+        | "disj_list"
+        | "bounded_quantification"
+        | "unbounded_quantification" => true,
+        "parentheses" => node.named_child(0).is_some_and(is_boolean),
+        "bound_prefix_op" => matches!(
+            actions::op_symbol(node),
+            Some("lnot" | "unchanged" | "enabled" | "always" | "eventually")
+        ),
+        "bound_infix_op" => matches!(
+            actions::op_symbol(node),
+            Some(
+                "eq" | "neq"
+                    | "lt"
+                    | "gt"
+                    | "leq"
+                    | "geq"
+                    | "in"
+                    | "notin"
+                    | "land"
+                    | "lor"
+                    | "implies"
+                    | "equiv"
+                    | "iff"
+                    | "subseteq"
+                    | "subset"
+                    | "supseteq"
+                    | "supset"
+            )
+        ),
+        _ => false,
+    }
+}
+
+/// The source tokens of `node`, ignoring whitespace and comments.
+fn tokens<'s>(ctx: &'s FileContext, node: Node) -> Vec<&'s str> {
+    let mut out = Vec::new();
+    traverse(node, &mut |n| {
+        if n.child_count() == 0 && !n.kind().contains("comment") {
+            out.push(text(ctx, n));
+        }
+    });
+    out
+}
+
+fn rule_undefined_identifiers(
+    ctx: &FileContext,
+    tree: &Tree,
+    symbols: &SymbolTable,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Without the full interface of every imported module we cannot tell a
+    // typo from a definition we failed to load.
+    if !ctx.imports.unresolved.is_empty() {
         return;
     }
 
-    if !has_init {
+    // Past a syntax error, recovery may have cut a parameter list or binder
+    // off from the names it introduces, or run on into the next definition,
+    // so the broken units are skipped. A definition swallowed that way is
+    // not reported as missing where it is used either.
+    let broken = broken_regions(tree);
+    let lost: HashSet<&str> = broken
+        .iter()
+        .flat_map(|r| lost_definitions(&ctx.src[r.clone()]))
+        .collect();
+    for reference in symbols.unresolved() {
+        if broken.iter().any(|r| r.contains(&reference.range.start)) {
+            continue;
+        }
+        // Bindings are final once the table is built, so a name that only
+        // resolves now is defined further down.
+        let Some(later) = symbols.lookup(reference.scope, &reference.name) else {
+            if lost.contains(reference.name.as_str()) {
+                continue;
+            }
+            diags.push(ctx.diagnostic(
+                reference.range.clone(),
+                RuleCode::TLA003,
+                format!("Undefined identifier `{}`", reference.name),
+            ));
+            continue;
+        };
+        let later = symbols.symbol(later);
+        let help = match later.kind {
+            SymbolKind::Operator => {
+                "move the definition above its first use, or declare it RECURSIVE"
+            }
+            _ => "move the definition above its first use",
+        };
         diags.push(Diagnostic {
-            file: ctx.path.to_string_lossy().into_owned(),
-            line: 1,
-            column: 1,
-            severity: Severity::Error,
-            code: RuleCode::TLA002,
-            message: "Module declares VARIABLES but is missing Init operator".to_string(),
+            labels: vec![ctx.label(later.name_range.clone(), "defined here")],
+            help: Some(help.to_string()),
+            ..ctx.diagnostic(
+                reference.range.clone(),
+                RuleCode::TLA003,
+                format!("`{}` is used before it is defined", reference.name),
+            )
         });
     }
-    if !has_next {
-        diags.push(Diagnostic {
-            file: ctx.path.to_string_lossy().into_owned(),
-            line: 1,
-            column: 1,
-            severity: Severity::Error,
-            code: RuleCode::TLA002,
-            message: "Module declares VARIABLES but is missing Next operator".to_string(),
-        });
+}
+
+/// The top-level unit around each outermost ERROR or MISSING node. An ERROR
+/// directly in the module often holds the rest of a definition the unit
+/// before it ran on into, so that unit is included as well.
+fn broken_regions(tree: &Tree) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            let unit = top_level_unit(node);
+            let mut start = unit;
+            if unit == node {
+                let mut prev = unit.prev_named_sibling();
+                while let Some(p) = prev.filter(|p| p.kind().ends_with("comment")) {
+                    prev = p.prev_named_sibling();
+                }
+                start = prev.unwrap_or(unit);
+            }
+            regions.push(start.start_byte()..unit.end_byte());
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
     }
+    regions
+}
+
+/// Names that `src` starts a line defining, as in `Name ==` or
+/// `Name(a, b) ==`.
+fn lost_definitions(src: &str) -> impl Iterator<Item = &str> {
+    src.lines().filter_map(|line| {
+        let line = line.trim_start();
+        let len = line
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(line.len());
+        let rest = line[len..].trim_start();
+        let rest = match rest.strip_prefix('(') {
+            Some(params) => params[params.find(')')? + 1..].trim_start(),
+            None => rest,
+        };
+        (len > 0 && rest.starts_with("==")).then(|| &line[..len])
+    })
 }
 
-fn child_identifiers(node: Node) -> Vec<Node> {
-    let mut idents = Vec::new();
+/// Whether the ERROR node `node` took in the module header.
+fn spans_header(node: Node) -> bool {
     let mut cursor = node.walk();
-    for child in node.children(&mut cursor) {
-        if child.kind() == "identifier" {
-            idents.push(child);
+    node.children(&mut cursor).any(|c| c.kind() == "MODULE")
+}
+
+fn has_error_descendant(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .any(|child| child.is_error() || has_error_descendant(child))
+}
+
+/// Picks the token to blame for an ERROR node. When recovery gave up on a
+/// whole module the node starts at the header, and the offending token is the
+/// last one the parser consumed rather than the first.
+fn unexpected_token(node: Node) -> Node {
+    let spans_header = spans_header(node);
+    let mut leaf = node;
+    while leaf.child_count() > 0 {
+        let idx = if spans_header {
+            leaf.child_count() - 1
+        } else {
+            0
+        };
+        match leaf.child(idx) {
+            Some(child) => leaf = child,
+            None => break,
         }
     }
-    idents
+    leaf
 }
 
-fn is_decl_node(node: Node) -> bool {
-    node.kind() == "variable_declaration" || node.kind() == "constant_declaration"
+/// Finds the innermost bracket, `IF` or `LET` opened before `node` (within
+/// its enclosing definition) that has not been completed yet, with the
+/// token that would complete it.
+fn unclosed(node: Node) -> Option<(Node, &'static str)> {
+    let mut scope = node;
+    while let Some(parent) = scope.parent() {
+        if parent.kind() == "source_file" {
+            break;
+        }
+        scope = parent;
+        if matches!(scope.kind(), "operator_definition" | "function_definition") {
+            break;
+        }
+    }
+
+    let mut open: Vec<(Node, &'static str)> = Vec::new();
+    let mut stack = vec![scope];
+    while let Some(current) = stack.pop() {
+        if current.start_byte() >= node.start_byte() {
+            continue;
+        }
+        if current.child_count() == 0 {
+            let top = open.last().map(|(_, expected)| *expected);
+            match current.kind() {
+                "(" => open.push((current, ")")),
+                "[" => open.push((current, "]")),
+                "{" => open.push((current, "}")),
+                "<<" => open.push((current, ">>")),
+                "IF" => open.push((current, "THEN")),
+                "LET" => open.push((current, "IN")),
+                ")" | "]" | "}" | ">>" | "]_" | ">>_" => {
+                    open.pop();
+                }
+                "THEN" if top == Some("THEN") => {
+                    if let Some(last) = open.last_mut() {
+                        last.1 = "ELSE";
+                    }
+                }
+                "ELSE" | "IN" if top == Some(current.kind()) => {
+                    open.pop();
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let mut cursor = current.walk();
+        let children: Vec<Node> = current.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    open.pop()
+}
+
+/// Recovery runs a definition that lacks an expression on into the next
+/// one, as in `Init ==` followed by `Next == ...`, and the error then sits
+/// at the `==` of the next definition. Points at the operator that lacks
+/// its operand instead.
+fn unfinished_definition(ctx: &FileContext, node: Node) -> Option<Diagnostic> {
+    let start = node.start_byte();
+    let line_start = ctx.src[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line = ctx.src[line_start..].lines().next()?;
+    let name = lost_definitions(line).next()?;
+    if start > line_start + line.find("==")? + 1 {
+        return None;
+    }
+    let before = ctx.src[..line_start].trim_end();
+    let op_start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let op = &before[op_start..];
+    let operand_ends = |c: char| c.is_alphanumeric() || matches!(c, '_' | '\'' | ')' | ']' | '}');
+    if op.is_empty() || op.ends_with(operand_ends) || op.ends_with(">>") {
+        return None;
+    }
+    Some(ctx.diagnostic(
+        op_start..before.len(),
+        RuleCode::TLA000,
+        format!(
+            "Syntax error: expected an expression after `{op}`, found the definition of `{name}`"
+        ),
+    ))
 }
 
-fn is_identifier_use(node: Node) -> bool {
-    matches!(node.kind(), "identifier" | "identifier_ref")
+fn describe_kind(node: Node) -> String {
+    if node.is_named() {
+        node.kind().replace('_', " ")
+    } else {
+        format!("`{}`", node.kind())
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut out: String = text.chars().take(max_chars).collect();
+        out.push_str("...");
+        out
+    }
 }
 
 fn traverse<F>(node: Node, f: &mut F)
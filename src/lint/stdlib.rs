@@ -0,0 +1,87 @@
+/// Identifier-named definitions exported by the standard modules that ship
+/// with tla2tools.jar, with their arities. Infix/prefix operators (`+`, `\o`,
+/// `:>`, ...) are not listed because they never appear as `identifier_ref`
+/// nodes. Modules that EXTEND another standard module include its exports.
+pub fn exports(module: &str) -> Option<&'static [(&'static str, usize)]> {
+    let names: &'static [(&'static str, usize)] = match module {
+        "Naturals" => &[("Nat", 0)],
+        "Integers" => &[("Nat", 0), ("Int", 0)],
+        "Reals" => &[("Nat", 0), ("Int", 0), ("Real", 0), ("Infinity", 0)],
+        "Sequences" => &[
+            ("Seq", 1),
+            ("Len", 1),
+            ("Append", 2),
+            ("Head", 1),
+            ("Tail", 1),
+            ("SubSeq", 3),
+            ("SelectSeq", 2),
+        ],
+        "FiniteSets" => &[("IsFiniteSet", 1), ("Cardinality", 1)],
+        "Bags" => &[
+            ("IsABag", 1),
+            ("BagToSet", 1),
+            ("SetToBag", 1),
+            ("BagIn", 2),
+            ("EmptyBag", 0),
+            ("BagUnion", 1),
+            ("SubBag", 1),
+            ("BagOfAll", 2),
+            ("BagCardinality", 1),
+            ("CopiesIn", 2),
+        ],
+        "TLC" => &[
+            ("Print", 2),
+            ("PrintT", 1),
+            ("Assert", 2),
+            ("JavaTime", 0),
+            ("TLCGet", 1),
+            ("TLCSet", 2),
+            ("Permutations", 1),
+            ("SortSeq", 2),
+            ("RandomElement", 1),
+            ("Any", 0),
+            ("ToString", 1),
+            ("TLCEval", 1),
+        ],
+        "RealTime" => &[
+            ("Nat", 0),
+            ("Int", 0),
+            ("Real", 0),
+            ("Infinity", 0),
+            ("RTBound", 4),
+            ("RTnow", 1),
+            ("now", 0),
+        ],
+        "Randomization" => &[
+            ("RandomSubset", 2),
+            ("RandomSetOfSubsets", 3),
+            ("TestRandomSetOfSubsets", 3),
+        ],
+        _ => return None,
+    };
+    Some(names)
+}
+
+/// Node kinds of the infix and prefix operators the standard modules define,
+/// as they appear in the `symbol` field of `bound_infix_op` and
+/// `bound_prefix_op`. Complements [`exports`] for usage checks.
+pub fn operator_symbols(module: &str) -> &'static [&'static str] {
+    const NATURALS: &[&str] = &[
+        "plus", "minus", "mul", "pow", "lt", "gt", "leq", "geq", "mod", "div", "dots_2",
+    ];
+    match module {
+        "Naturals" => NATURALS,
+        "Integers" => &[
+            "plus", "minus", "mul", "pow", "lt", "gt", "leq", "geq", "mod", "div", "dots_2",
+            "negative",
+        ],
+        "Reals" | "RealTime" => &[
+            "plus", "minus", "mul", "pow", "lt", "gt", "leq", "geq", "mod", "div", "dots_2",
+            "negative", "slash",
+        ],
+        "Sequences" => &["circ"],
+        "Bags" => &["oplus", "ominus", "sqsubseteq"],
+        "TLC" => &["map_to", "compose"],
+        _ => &[],
+    }
+}
@@ -0,0 +1,213 @@
+use crate::lint::FileContext;
+use crate::lint::rules::CHECKS;
+use crate::lint::types::{Diagnostic, RuleCode};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use tree_sitter::{Node, Tree};
+
+const PREFIX: &str = "tla-lint:";
+
+/// One `tla-lint:` comment and the lines it silences.
+struct Suppression {
+    /// `None` silences every rule.
+    code: Option<RuleCode>,
+    lines: RangeInclusive<usize>,
+    /// Byte range of the comment, to report it when unused.
+    comment: (usize, usize),
+    used: bool,
+}
+
+/// Drops diagnostics silenced by `tla-lint:` comments:
+///
+/// - `\* tla-lint: disable-next-line TLA001` silences the following line,
+/// - `(* tla-lint: disable TLA003 *)` up to `(* tla-lint: enable TLA003 *)`
+///   or the end of the file silences a region,
+/// - `\* tla-lint: disable-file TLA002` silences the whole file.
+///
+/// Leaving out the codes silences every rule. Suppressions that silence
+/// nothing, and malformed ones, are reported as TLA029; those for rules that
+/// did not run, as listed in `ran`, are not, since they may be needed once
+/// the file parses again.
+pub fn apply(ctx: &FileContext, tree: &Tree, ran: &HashSet<RuleCode>, diags: &mut Vec<Diagnostic>) {
+    let mut suppressions = Vec::new();
+    let mut problems = Vec::new();
+    // Open `disable` regions by code, with their start line and comment.
+    let mut open: HashMap<Option<RuleCode>, (usize, (usize, usize))> = HashMap::new();
+
+    for (start, end) in comments(ctx, tree.root_node()) {
+        let Some(directive) = directive(&ctx.src[start..end]) else {
+            continue;
+        };
+        let range = (start, end);
+        let (line, _) = ctx.position(start);
+        let (end_line, _) = ctx.position(end);
+        let mut words = directive.split(|c: char| c.is_whitespace() || c == ',');
+        let command = words.next().unwrap_or_default();
+        let words: Vec<&str> = words.filter(|w| !w.is_empty()).collect();
+        let mut codes = Vec::new();
+        for word in &words {
+            match word.parse::<RuleCode>() {
+                Ok(code) => codes.push(Some(code)),
+                Err(()) => {
+                    problems.push((range, format!("Unknown rule code `{word}` in suppression")))
+                }
+            }
+        }
+        if words.is_empty() {
+            codes.push(None);
+        }
+
+        let lines = match command {
+            "disable-next-line" => end_line + 1..=end_line + 1,
+            "disable-file" => 0..=usize::MAX,
+            "disable" => {
+                for code in codes {
+                    open.entry(code).or_insert((line, range));
+                }
+                continue;
+            }
+            "enable" => {
+                let closing: Vec<Option<RuleCode>> = if codes == [None] {
+                    open.keys().copied().collect()
+                } else {
+                    codes
+                };
+                if closing.is_empty() {
+                    problems.push((range, "`enable` without a preceding `disable`".to_string()));
+                }
+                for code in closing {
+                    match open.remove(&code) {
+                        Some((start, comment)) => suppressions.push(Suppression {
+                            code,
+                            lines: start..=line,
+                            comment,
+                            used: false,
+                        }),
+                        None => problems.push((
+                            range,
+                            format!(
+                                "`enable {}` without a preceding `disable`",
+                                code.map(|c| c.to_string()).unwrap_or_default()
+                            ),
+                        )),
+                    }
+                }
+                continue;
+            }
+            _ => {
+                problems.push((
+                    range,
+                    format!(
+                        "Unknown suppression `{command}`; expected `disable-next-line`, \
+                         `disable`, `enable` or `disable-file`"
+                    ),
+                ));
+                continue;
+            }
+        };
+        suppressions.extend(codes.into_iter().map(|code| Suppression {
+            code,
+            lines: lines.clone(),
+            comment: range,
+            used: false,
+        }));
+    }
+    suppressions.extend(
+        open.into_iter()
+            .map(|(code, (start, comment))| Suppression {
+                code,
+                lines: start..=usize::MAX,
+                comment,
+                used: false,
+            }),
+    );
+
+    diags.retain(|d| {
+        let mut silenced = false;
+        for s in suppressions.iter_mut() {
+            if s.lines.contains(&d.line) && s.code.is_none_or(|code| code == d.code) {
+                s.used = true;
+                silenced = true;
+            }
+        }
+        !silenced
+    });
+
+    let all_ran = CHECKS
+        .iter()
+        .flat_map(|c| c.codes)
+        .filter(|c| ctx.options.rules.is_enabled(**c))
+        .all(|c| ran.contains(c));
+    let checked = |s: &&Suppression| s.code.map_or(all_ran, |code| ran.contains(&code));
+    for s in suppressions.iter().filter(|s| !s.used).filter(checked) {
+        let what = s
+            .code
+            .map_or("diagnostics".to_string(), |c| format!("{c} diagnostics"));
+        problems.push((
+            s.comment,
+            format!("Unused suppression: no {what} to silence"),
+        ));
+    }
+    problems.sort_by_key(|((start, _), _)| *start);
+    for ((start, end), message) in problems {
+        diags.push(ctx.diagnostic(start..end, RuleCode::TLA029, message));
+    }
+}
+
+/// Byte ranges of the line and block comments, in source order. The
+/// grammar joins block comments on consecutive lines into one node, so
+/// those are split again.
+fn comments(ctx: &FileContext, root: Node) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        match node.kind() {
+            "comment" => out.push((node.start_byte(), node.end_byte())),
+            "block_comment" => out.extend(block_comments(ctx, node)),
+            _ => {
+                let mut cursor = node.walk();
+                stack.extend(node.children(&mut cursor));
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+/// The outermost `(* ... *)` pairs inside a `block_comment` node.
+fn block_comments(ctx: &FileContext, node: Node) -> Vec<(usize, usize)> {
+    let bytes = ctx.src.as_bytes();
+    let mut out = Vec::new();
+    let mut depth = 0;
+    let mut start = node.start_byte();
+    let mut i = node.start_byte();
+    while i + 1 < node.end_byte() {
+        match &bytes[i..i + 2] {
+            b"(*" => {
+                if depth == 0 {
+                    start = i;
+                }
+                depth += 1;
+                i += 2;
+            }
+            b"*)" if depth > 0 => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    out.push((start, i));
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    out
+}
+
+/// The text after `tla-lint:` when `comment` is a directive.
+fn directive(comment: &str) -> Option<&str> {
+    let body = match comment.strip_prefix("(*") {
+        Some(block) => block.strip_suffix("*)").unwrap_or(block),
+        None => comment.strip_prefix("\\*")?,
+    };
+    body.trim().strip_prefix(PREFIX).map(str::trim)
+}
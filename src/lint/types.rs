@@ -1,18 +1,100 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Warning,
     Error,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum RuleCode {
     TLA000,
     TLA001,
     TLA002,
+    TLA003,
+    TLA004,
+    TLA005,
+    TLA006,
+    TLA007,
+    TLA008,
+    TLA009,
+    TLA010,
+    TLA011,
+    TLA012,
+    TLA013,
+    TLA014,
+    TLA015,
+    TLA016,
+    TLA017,
+    TLA018,
+    TLA019,
+    TLA020,
+    TLA021,
+    TLA022,
+    TLA023,
+    TLA024,
+    TLA025,
+    TLA026,
+    TLA027,
+    TLA028,
+    TLA029,
+}
+
+impl RuleCode {
+    pub const ALL: &'static [RuleCode] = &[
+        RuleCode::TLA000,
+        RuleCode::TLA001,
+        RuleCode::TLA002,
+        RuleCode::TLA003,
+        RuleCode::TLA004,
+        RuleCode::TLA005,
+        RuleCode::TLA006,
+        RuleCode::TLA007,
+        RuleCode::TLA008,
+        RuleCode::TLA009,
+        RuleCode::TLA010,
+        RuleCode::TLA011,
+        RuleCode::TLA012,
+        RuleCode::TLA013,
+        RuleCode::TLA014,
+        RuleCode::TLA015,
+        RuleCode::TLA016,
+        RuleCode::TLA017,
+        RuleCode::TLA018,
+        RuleCode::TLA019,
+        RuleCode::TLA020,
+        RuleCode::TLA021,
+        RuleCode::TLA022,
+        RuleCode::TLA023,
+        RuleCode::TLA024,
+        RuleCode::TLA025,
+        RuleCode::TLA026,
+        RuleCode::TLA027,
+        RuleCode::TLA028,
+        RuleCode::TLA029,
+    ];
+}
+
+impl fmt::Display for RuleCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for RuleCode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RuleCode::ALL
+            .iter()
+            .copied()
+            .find(|code| code.to_string() == s)
+            .ok_or(())
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -20,7 +102,66 @@ pub struct Diagnostic {
     pub file: String,
     pub line: usize,   // 1-based
     pub column: usize, // 1-based, character column
+    pub end_line: Option<usize>,
+    pub end_column: Option<usize>,
+    /// Byte offsets of the reported span, when there is one.
+    pub start_byte: Option<usize>,
+    pub end_byte: Option<usize>,
     pub severity: Severity,
     pub code: RuleCode,
     pub message: String,
+    /// Secondary spans, such as where a duplicate was first defined.
+    pub labels: Vec<Label>,
+    /// Background that does not fit in the message.
+    pub notes: Vec<String>,
+    /// How to resolve the diagnostic.
+    pub help: Option<String>,
+    /// A machine-applicable fix, offered by `tla lint --fix` and editors.
+    pub fix: Option<Fix>,
+}
+
+/// A secondary span of a diagnostic in the same file, with what it shows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Label {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub message: String,
+}
+
+/// Edits that resolve a diagnostic. They are applied together or not at all.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Fix {
+    pub description: String,
+    pub edits: Vec<Edit>,
+}
+
+/// Replaces the bytes `start..end` of the file with `replacement`; an
+/// insertion has `start == end`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn insert(at: usize, text: impl Into<String>) -> Self {
+        Edit {
+            start: at,
+            end: at,
+            replacement: text.into(),
+        }
+    }
+
+    pub fn delete(start: usize, end: usize) -> Self {
+        Edit {
+            start,
+            end,
+            replacement: String::new(),
+        }
+    }
 }
@@ -0,0 +1,401 @@
+use crate::lint::cfg::{self, TlcConfig};
+use crate::lint::stdlib;
+use crate::semantic::{declared_arity, parameters, unit_names};
+use crate::tla_parser::TlaParser;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use tree_sitter::{Node, Tree};
+
+/// Names a module makes available to modules that EXTEND or INSTANCE it.
+#[derive(Debug, Default)]
+struct ModuleInterface {
+    /// Non-LOCAL definitions, including those it EXTENDs or INSTANCEs,
+    /// with their arities.
+    definitions: HashMap<String, usize>,
+    /// VARIABLES and CONSTANTS, which only EXTENDS brings into scope.
+    declarations: HashMap<String, usize>,
+    /// Node kinds of the user-defined infix, prefix and postfix operators,
+    /// e.g. `plusplus` for `a ++ b == ...`.
+    symbols: HashSet<String>,
+    /// Modules on the import chain that could not be found.
+    unresolved: Vec<String>,
+}
+
+/// Names brought into a module by its EXTENDS and unnamed INSTANCE units.
+#[derive(Clone, Debug, Default)]
+pub struct Imports {
+    pub names: HashSet<String>,
+    /// Number of arguments each imported name takes.
+    pub arities: HashMap<String, usize>,
+    /// Imported modules that are neither standard modules nor `.tla` files
+    /// next to the importing file. Name resolution is incomplete when this
+    /// is non-empty.
+    pub unresolved: Vec<String>,
+    /// What each EXTENDed module provides, keyed by module name. Modules
+    /// whose import chain is not fully resolved are left out.
+    pub extended: HashMap<String, ModuleExports>,
+}
+
+/// Everything a module makes available to a module that EXTENDs it.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleExports {
+    /// Definitions, VARIABLES and CONSTANTS.
+    pub names: HashSet<String>,
+    /// Node kinds of the operator symbols it defines, see
+    /// [`stdlib::operator_symbols`].
+    pub symbols: HashSet<String>,
+}
+
+/// Uses of a module's names from outside the module itself.
+#[derive(Clone, Debug, Default)]
+pub struct ExternalUses {
+    /// Names referenced by sibling modules that EXTEND or INSTANCE it.
+    pub modules: HashSet<String>,
+    /// What the TLC `.cfg` files next to it refer to.
+    pub config: TlcConfig,
+}
+
+/// A sibling `.tla` file, summarized for cross-module usage checks.
+#[derive(Debug)]
+struct Sibling {
+    path: PathBuf,
+    imports: HashSet<String>,
+    references: HashSet<String>,
+}
+
+/// Resolves imported modules the way SANY does: standard modules are known
+/// by name, user modules are looked up next to the importing file.
+pub struct Workspace {
+    parser: TlaParser,
+    cache: HashMap<PathBuf, Rc<ModuleInterface>>,
+    loading: HashSet<PathBuf>,
+    siblings: HashMap<PathBuf, Rc<Vec<Sibling>>>,
+    configs: HashMap<PathBuf, Rc<TlcConfig>>,
+}
+
+impl Workspace {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            parser: TlaParser::new()?,
+            cache: HashMap::new(),
+            loading: HashSet::new(),
+            siblings: HashMap::new(),
+            configs: HashMap::new(),
+        })
+    }
+
+    /// Collects references to the module in `tree` from the other `.tla`
+    /// files and the `.cfg` files in its directory.
+    pub fn external_uses(&mut self, path: &Path, src: &str, tree: &Tree) -> ExternalUses {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut uses = ExternalUses {
+            config: (*self.configs_in(dir)).clone(),
+            ..ExternalUses::default()
+        };
+
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let names: Vec<String> = root
+            .children(&mut cursor)
+            .filter(|n| n.kind() == "module")
+            .filter_map(|m| m.child_by_field_name("name"))
+            .map(|n| text(n, src))
+            .collect();
+        for sibling in self.siblings_in(dir).iter() {
+            if same_file(&sibling.path, path) {
+                continue;
+            }
+            if names.iter().any(|n| sibling.imports.contains(n)) {
+                uses.modules.extend(sibling.references.iter().cloned());
+            }
+        }
+        uses
+    }
+
+    fn siblings_in(&mut self, dir: &Path) -> Rc<Vec<Sibling>> {
+        if let Some(found) = self.siblings.get(dir) {
+            return Rc::clone(found);
+        }
+        let mut siblings = Vec::new();
+        for path in files_with_extension(dir, "tla") {
+            let Ok(src) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let Some(tree) = self.parser.parse(&src) else {
+                continue;
+            };
+            let mut sibling = Sibling {
+                path,
+                imports: HashSet::new(),
+                references: HashSet::new(),
+            };
+            collect_imports_and_references(tree.root_node(), &src, &mut sibling);
+            siblings.push(sibling);
+        }
+        let siblings = Rc::new(siblings);
+        self.siblings
+            .insert(dir.to_path_buf(), Rc::clone(&siblings));
+        siblings
+    }
+
+    fn configs_in(&mut self, dir: &Path) -> Rc<TlcConfig> {
+        if let Some(found) = self.configs.get(dir) {
+            return Rc::clone(found);
+        }
+        let mut merged = TlcConfig::default();
+        for path in files_with_extension(dir, "cfg") {
+            if let Ok(src) = fs::read_to_string(&path) {
+                let config = cfg::parse(&src);
+                merged.operators.extend(config.operators);
+                merged.constants.extend(config.constants);
+                merged.invariants.extend(config.invariants);
+                merged.specifications.extend(config.specifications);
+            }
+        }
+        let merged = Rc::new(merged);
+        self.configs.insert(dir.to_path_buf(), Rc::clone(&merged));
+        merged
+    }
+
+    pub fn imports(&mut self, path: &Path, src: &str, tree: &Tree) -> Imports {
+        let mut imports = Imports::default();
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        for module in root.children(&mut cursor).filter(|n| n.kind() == "module") {
+            let iface = self.interface_of(module, src, dir, true);
+            for (name, arity) in iface.definitions.into_iter().chain(iface.declarations) {
+                imports.names.insert(name.clone());
+                imports.arities.insert(name, arity);
+            }
+            imports.unresolved.extend(iface.unresolved);
+
+            for name in extended_modules(module, src) {
+                let iface = self.load(&name, dir);
+                if !iface.unresolved.is_empty() {
+                    continue;
+                }
+                let exports = ModuleExports {
+                    names: iface
+                        .definitions
+                        .keys()
+                        .chain(iface.declarations.keys())
+                        .cloned()
+                        .collect(),
+                    symbols: iface.symbols.clone(),
+                };
+                imports.extended.insert(name, exports);
+            }
+        }
+        imports
+    }
+
+    fn load(&mut self, name: &str, dir: &Path) -> Rc<ModuleInterface> {
+        let path = dir.join(format!("{name}.tla"));
+        if let Some(iface) = self.cache.get(&path) {
+            return Rc::clone(iface);
+        }
+
+        let iface = if path.is_file() {
+            if !self.loading.insert(path.clone()) {
+                // Circular EXTENDS; SANY reports it, we just stop recursing.
+                return Rc::new(ModuleInterface::default());
+            }
+            let iface = fs::read_to_string(&path)
+                .ok()
+                .and_then(|src| self.parser.parse(&src).map(|tree| (src, tree)))
+                .and_then(|(src, tree)| {
+                    let root = tree.root_node();
+                    let mut cursor = root.walk();
+                    let module = root.children(&mut cursor).find(|n| n.kind() == "module")?;
+                    let mut iface = self.interface_of(module, &src, dir, false);
+                    for unit in top_level_units(module, false) {
+                        iface.symbols.extend(operator_symbol(unit));
+                        let names = unit_names(unit)
+                            .into_iter()
+                            .map(|n| (text(n, &src), unit_arity(unit, n)));
+                        if is_declaration(unit) {
+                            iface.declarations.extend(names);
+                        } else {
+                            iface.definitions.extend(names);
+                        }
+                    }
+                    Some(iface)
+                })
+                .unwrap_or_else(|| ModuleInterface {
+                    unresolved: vec![name.to_string()],
+                    ..ModuleInterface::default()
+                });
+            self.loading.remove(&path);
+            iface
+        } else if let Some(names) = stdlib::exports(name) {
+            ModuleInterface {
+                definitions: names.iter().map(|(n, a)| (n.to_string(), *a)).collect(),
+                symbols: stdlib::operator_symbols(name)
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                ..ModuleInterface::default()
+            }
+        } else {
+            ModuleInterface {
+                unresolved: vec![name.to_string()],
+                ..ModuleInterface::default()
+            }
+        };
+
+        let iface = Rc::new(iface);
+        self.cache.insert(path, Rc::clone(&iface));
+        iface
+    }
+
+    /// Collects what `module` imports through EXTENDS and unnamed INSTANCE.
+    /// LOCAL INSTANCEs count only when `include_local` is set, i.e. when
+    /// resolving names inside `module` itself rather than re-exporting them.
+    fn interface_of(
+        &mut self,
+        module: Node,
+        src: &str,
+        dir: &Path,
+        include_local: bool,
+    ) -> ModuleInterface {
+        let mut out = ModuleInterface::default();
+        for name in extended_modules(module, src) {
+            let iface = self.load(&name, dir);
+            out.definitions.extend(iface.definitions.clone());
+            out.declarations.extend(iface.declarations.clone());
+            out.symbols.extend(iface.symbols.iter().cloned());
+            out.unresolved.extend(iface.unresolved.iter().cloned());
+        }
+        for unit in top_level_units(module, include_local) {
+            if unit.kind() != "instance" {
+                continue;
+            }
+            if let Some(name) = unit.named_child(0) {
+                let iface = self.load(text(name, src).as_str(), dir);
+                out.definitions.extend(iface.definitions.clone());
+                out.symbols.extend(iface.symbols.iter().cloned());
+                out.unresolved.extend(iface.unresolved.iter().cloned());
+            }
+        }
+        out
+    }
+}
+
+fn collect_imports_and_references(node: Node, src: &str, out: &mut Sibling) {
+    match node.kind() {
+        "extends" => {
+            let mut cursor = node.walk();
+            out.imports
+                .extend(node.named_children(&mut cursor).map(|n| text(n, src)));
+            return;
+        }
+        "instance" => {
+            if let Some(name) = node.named_child(0) {
+                out.imports.insert(text(name, src));
+            }
+        }
+        "identifier_ref" => {
+            out.references.insert(text(node, src));
+        }
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_imports_and_references(child, src, out);
+    }
+}
+
+fn files_with_extension(dir: &Path, ext: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case(ext))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn extended_modules(module: Node, src: &str) -> Vec<String> {
+    let mut cursor = module.walk();
+    module
+        .children(&mut cursor)
+        .filter(|n| n.kind() == "extends")
+        .flat_map(|ext| {
+            let mut inner = ext.walk();
+            ext.named_children(&mut inner)
+                .map(|n| text(n, src))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The number of arguments `name`, introduced by `unit`, takes.
+fn unit_arity(unit: Node, name: Node) -> usize {
+    match name.parent() {
+        Some(decl) if decl.kind() == "operator_declaration" => declared_arity(decl),
+        _ if unit.kind() == "operator_definition" => parameters(unit).len(),
+        _ => 0,
+    }
+}
+
+/// The node kind of the operator symbol `unit` defines, if it defines an
+/// infix, prefix or postfix operator rather than a named one.
+fn operator_symbol(unit: Node) -> Option<String> {
+    if unit.kind() != "operator_definition" {
+        return None;
+    }
+    let name = unit.child_by_field_name("name")?;
+    if name.kind() == "identifier" {
+        return None;
+    }
+    name.named_child(0).map(|symbol| symbol.kind().to_string())
+}
+
+/// Direct children of `module`, unwrapping LOCAL definitions only when
+/// `include_local` is set.
+fn top_level_units(module: Node, include_local: bool) -> Vec<Node> {
+    let mut cursor = module.walk();
+    module
+        .children(&mut cursor)
+        .filter_map(|unit| {
+            if unit.kind() == "local_definition" {
+                if include_local {
+                    unit.named_child(0)
+                } else {
+                    None
+                }
+            } else {
+                Some(unit)
+            }
+        })
+        .collect()
+}
+
+fn is_declaration(unit: Node) -> bool {
+    matches!(unit.kind(), "variable_declaration" | "constant_declaration")
+}
+
+fn text(node: Node, src: &str) -> String {
+    node.utf8_text(src.as_bytes())
+        .unwrap_or_default()
+        .to_string()
+}
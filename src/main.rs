@@ -1,18 +1,22 @@
-mod check;
-mod cli;
-mod doctor;
-mod fmt;
-mod lint;
-mod tla_parser;
-mod tooling;
-
 use anyhow::Result;
 use clap::Parser;
+use tla_cli::{check, cli, doctor, fmt, lint};
 
 fn main() -> Result<()> {
     let cmd = cli::Cli::parse();
     match cmd.command {
-        cli::Command::Lint { paths, json } => lint::run(paths, json)?,
+        cli::Command::Lint {
+            list_rules: true,
+            json,
+            ..
+        } => lint::list_rules(json)?,
+        cli::Command::Lint {
+            explain: Some(code),
+            ..
+        } => lint::explain(&code)?,
+        cli::Command::Lint {
+            paths, json, fix, ..
+        } => lint::run(paths, json, fix)?,
         cli::Command::Fmt { paths } => fmt::run(paths)?,
         cli::Command::Check { spec, cfg } => check::run(spec, cfg)?,
         cli::Command::Doctor {
@@ -0,0 +1,687 @@
+//! Scope-aware symbol table built on top of the tree-sitter parse tree.
+//!
+//! [`SymbolTable::build`] walks a parsed module once, creating a [`Scope`]
+//! for every construct that introduces names (the module itself, LET,
+//! operator parameters, quantifiers, set comprehensions, function
+//! constructors, CHOOSE, LAMBDA and ASSUME/PROVE), a [`Symbol`] for every
+//! name introduced, and a [`Reference`] for every `identifier_ref`, resolved
+//! against the innermost enclosing binding made before it. As in SANY, a
+//! definition is only visible below it, unless a RECURSIVE declaration
+//! introduced the operator earlier.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use tree_sitter::{Node, Tree};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(pub usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(pub usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// Names imported through EXTENDS and INSTANCE; the root of every table.
+    Imports,
+    Module,
+    Let,
+    OperatorParameters,
+    Quantifier,
+    SetComprehension,
+    FunctionConstructor,
+    Choose,
+    Lambda,
+    AssumeProve,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Imported,
+    Variable,
+    Constant,
+    Operator,
+    Function,
+    /// `M == INSTANCE N`
+    ModuleInstance,
+    /// `RECURSIVE Op(_)`, resolved only while no definition exists.
+    RecursiveDeclaration,
+    Theorem,
+    Assumption,
+    Parameter,
+    BoundVariable,
+}
+
+#[derive(Clone, Debug)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    /// Byte range of the syntax node that opens the scope.
+    pub range: Range<usize>,
+    pub symbols: Vec<SymbolId>,
+    bindings: HashMap<String, SymbolId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub scope: ScopeId,
+    /// Byte range of the introducing identifier (empty for imports).
+    pub name_range: Range<usize>,
+    /// Byte range of the whole declaring construct, e.g. the
+    /// `operator_definition` or `quantifier_bound`.
+    pub definition_range: Range<usize>,
+    /// Number of arguments the symbol takes (0 for values).
+    pub arity: usize,
+    pub local: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub name: String,
+    pub range: Range<usize>,
+    pub scope: ScopeId,
+    pub symbol: Option<SymbolId>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    scopes: Vec<Scope>,
+    symbols: Vec<Symbol>,
+    references: Vec<Reference>,
+    /// Start byte of each reference to its index in `references`.
+    reference_index: HashMap<usize, usize>,
+}
+
+impl SymbolTable {
+    /// Builds the table for every module in `tree`. `imported` lists names
+    /// that EXTENDS/INSTANCE bring in; they resolve when nothing in the file
+    /// binds the same name.
+    pub fn build<I, S>(tree: &Tree, src: &str, imported: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut builder = Builder {
+            src,
+            table: SymbolTable::default(),
+            stack: Vec::new(),
+        };
+        let root = tree.root_node();
+        let imports = builder.push_scope(ScopeKind::Imports, root);
+        for name in imported {
+            builder.table.add_symbol(Symbol {
+                name: name.into(),
+                kind: SymbolKind::Imported,
+                scope: imports,
+                name_range: 0..0,
+                definition_range: 0..0,
+                arity: 0,
+                local: false,
+            });
+        }
+        builder.walk(root);
+        builder.table
+    }
+
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0]
+    }
+
+    pub fn symbol_ids(&self) -> impl Iterator<Item = SymbolId> + '_ {
+        (0..self.symbols.len()).map(SymbolId)
+    }
+
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    pub fn references_to(&self, id: SymbolId) -> impl Iterator<Item = &Reference> + '_ {
+        self.references.iter().filter(move |r| r.symbol == Some(id))
+    }
+
+    /// The reference whose `identifier_ref` starts at `byte_offset`.
+    pub fn reference_at(&self, byte_offset: usize) -> Option<&Reference> {
+        self.reference_index
+            .get(&byte_offset)
+            .map(|idx| &self.references[*idx])
+    }
+
+    /// The symbol an `identifier_ref` node resolves to.
+    pub fn resolve(&self, node: Node) -> Option<SymbolId> {
+        self.reference_at(node.start_byte()).and_then(|r| r.symbol)
+    }
+
+    pub fn unresolved(&self) -> impl Iterator<Item = &Reference> + '_ {
+        self.references.iter().filter(|r| r.symbol.is_none())
+    }
+
+    /// Symbols declared directly in module scopes (not imports, not LET).
+    pub fn module_symbols(&self) -> impl Iterator<Item = (SymbolId, &Symbol)> + '_ {
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| self.scopes[s.scope.0].kind == ScopeKind::Module)
+            .map(|(i, s)| (SymbolId(i), s))
+    }
+
+    /// Resolves `name` as seen from `scope`, walking outwards.
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            let scope = &self.scopes[id.0];
+            if let Some(sym) = scope.bindings.get(name) {
+                return Some(*sym);
+            }
+            current = scope.parent;
+        }
+        None
+    }
+
+    /// The innermost scope containing `byte_offset`.
+    pub fn scope_at(&self, byte_offset: usize) -> ScopeId {
+        let mut best = ScopeId(0);
+        for (idx, scope) in self.scopes.iter().enumerate() {
+            if scope.range.contains(&byte_offset)
+                && scope.range.len() <= self.scopes[best.0].range.len()
+            {
+                best = ScopeId(idx);
+            }
+        }
+        best
+    }
+
+    /// The symbol defined or referenced at `byte_offset`, for go-to-definition,
+    /// find-references, rename and hover.
+    pub fn symbol_at(&self, byte_offset: usize) -> Option<SymbolId> {
+        if let Some(idx) = self
+            .symbols
+            .iter()
+            .position(|s| s.name_range.contains(&byte_offset))
+        {
+            return Some(SymbolId(idx));
+        }
+        self.references
+            .iter()
+            .find(|r| r.range.contains(&byte_offset))
+            .and_then(|r| r.symbol)
+    }
+
+    fn add_symbol(&mut self, symbol: Symbol) -> SymbolId {
+        let id = SymbolId(self.symbols.len());
+        let scope = &mut self.scopes[symbol.scope.0];
+        scope.symbols.push(id);
+        // First definition wins; a RECURSIVE forward declaration gives way to
+        // the definition that follows it, which also takes over the
+        // references made through the declaration.
+        let existing = scope.bindings.get(&symbol.name).copied();
+        let forward = existing.filter(|e| {
+            self.symbols[e.0].kind == SymbolKind::RecursiveDeclaration
+                && symbol.kind != SymbolKind::RecursiveDeclaration
+        });
+        if existing.is_none() || forward.is_some() {
+            scope.bindings.insert(symbol.name.clone(), id);
+        }
+        if let Some(declaration) = forward {
+            for reference in &mut self.references {
+                if reference.symbol == Some(declaration) {
+                    reference.symbol = Some(id);
+                }
+            }
+        }
+        self.symbols.push(symbol);
+        id
+    }
+}
+
+/// Finds the `kind` node spanning exactly `range`, e.g. the
+/// `operator_definition` behind a [`Symbol::definition_range`].
+pub fn node_for_range<'t>(root: Node<'t>, range: &Range<usize>, kind: &str) -> Option<Node<'t>> {
+    let mut node = root.descendant_for_byte_range(range.start, range.end)?;
+    while node.byte_range() == *range {
+        if node.kind() == kind {
+            return Some(node);
+        }
+        node = node.parent()?;
+    }
+    None
+}
+
+/// The identifiers a single module unit introduces.
+pub fn unit_names(unit: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    match unit.kind() {
+        "variable_declaration" | "constant_declaration" | "recursive_declaration" => {
+            let mut cursor = unit.walk();
+            for child in unit.named_children(&mut cursor) {
+                match child.kind() {
+                    "identifier" => out.push(child),
+                    "operator_declaration" => out.extend(child.child_by_field_name("name")),
+                    _ => {}
+                }
+            }
+        }
+        "operator_definition"
+        | "function_definition"
+        | "module_definition"
+        | "theorem"
+        | "assumption" => {
+            out.extend(
+                unit.child_by_field_name("name")
+                    .filter(|n| n.kind() == "identifier"),
+            );
+        }
+        _ => {}
+    }
+    out
+}
+
+/// Identifiers introduced by the `intro` of a `quantifier_bound`, `choose`
+/// or unbounded quantifier, flattening `<<a, b>>` tuples.
+pub fn intro_names(node: Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    let mut cursor = node.walk();
+    for intro in node.children_by_field_name("intro", &mut cursor) {
+        match intro.kind() {
+            "identifier" => out.push(intro),
+            "tuple_of_identifiers" => {
+                let mut inner = intro.walk();
+                out.extend(
+                    intro
+                        .named_children(&mut inner)
+                        .filter(|n| n.kind() == "identifier"),
+                );
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Parameters of an operator or module definition. Higher-order parameters
+/// such as `F(_)` are returned as their `operator_declaration` node.
+pub fn parameters(def: Node) -> Vec<Node> {
+    let mut cursor = def.walk();
+    def.children_by_field_name("parameter", &mut cursor)
+        .filter(|p| matches!(p.kind(), "identifier" | "operator_declaration"))
+        .collect()
+}
+
+/// Number of `_` placeholders in an `operator_declaration` such as `F(_, _)`.
+pub fn declared_arity(decl: Node) -> usize {
+    let mut cursor = decl.walk();
+    decl.children_by_field_name("parameter", &mut cursor)
+        .filter(|p| p.kind() == "placeholder")
+        .count()
+}
+
+fn text<'s>(node: Node, src: &'s str) -> &'s str {
+    node.utf8_text(src.as_bytes()).unwrap_or_default()
+}
+
+struct Builder<'s> {
+    src: &'s str,
+    table: SymbolTable,
+    stack: Vec<ScopeId>,
+}
+
+impl Builder<'_> {
+    fn walk(&mut self, node: Node) {
+        match node.kind() {
+            "identifier_ref" => self.reference(node),
+            "module" => {
+                self.push_scope(ScopeKind::Module, node);
+                let mut cursor = node.walk();
+                let units: Vec<Node> = node.named_children(&mut cursor).collect();
+                // TLA+ names must be defined before they are used; only a
+                // RECURSIVE declaration makes an operator visible earlier.
+                for unit in units {
+                    self.declare_unit(unit);
+                    if unit.kind() != "extends" {
+                        self.walk(unit);
+                    }
+                }
+                self.pop_scope();
+            }
+            "local_definition" => self.walk_children(node),
+            "operator_definition" | "module_definition" => {
+                self.push_scope(ScopeKind::OperatorParameters, node);
+                for param in parameters(node) {
+                    self.declare_parameter(param, node);
+                }
+                if let Some(def) = node.child_by_field_name("definition") {
+                    self.walk(def);
+                }
+                self.pop_scope();
+            }
+            "function_definition" | "function_literal" => {
+                self.walk_bounded(ScopeKind::FunctionConstructor, node)
+            }
+            "bounded_quantification" => self.walk_bounded(ScopeKind::Quantifier, node),
+            "set_filter" | "set_map" => self.walk_bounded(ScopeKind::SetComprehension, node),
+            "choose" => {
+                if let Some(set) = node.child_by_field_name("set") {
+                    self.walk(set);
+                }
+                self.push_scope(ScopeKind::Choose, node);
+                for ident in intro_names(node) {
+                    self.declare(ident, SymbolKind::BoundVariable, node, 0);
+                }
+                if let Some(body) = node.child_by_field_name("expression") {
+                    self.walk(body);
+                }
+                self.pop_scope();
+            }
+            "unbounded_quantification" => {
+                self.push_scope(ScopeKind::Quantifier, node);
+                for ident in intro_names(node) {
+                    self.declare(ident, SymbolKind::BoundVariable, node, 0);
+                }
+                if let Some(body) = node.child_by_field_name("expression") {
+                    self.walk(body);
+                }
+                self.pop_scope();
+            }
+            "lambda" => {
+                self.push_scope(ScopeKind::Lambda, node);
+                let mut cursor = node.walk();
+                let children: Vec<Node> = node.named_children(&mut cursor).collect();
+                for param in children.iter().filter(|c| c.kind() == "identifier") {
+                    self.declare(*param, SymbolKind::Parameter, node, 0);
+                }
+                for child in children.iter().filter(|c| c.kind() != "identifier") {
+                    self.walk(*child);
+                }
+                self.pop_scope();
+            }
+            "let_in" => {
+                self.push_scope(ScopeKind::Let, node);
+                let mut cursor = node.walk();
+                let defs: Vec<Node> = node
+                    .children_by_field_name("definitions", &mut cursor)
+                    .collect();
+                for def in defs {
+                    self.declare_unit(def);
+                    self.walk(def);
+                }
+                if let Some(body) = node.child_by_field_name("expression") {
+                    self.walk(body);
+                }
+                self.pop_scope();
+            }
+            "instance" => {
+                // The first reference names the instantiated module; the
+                // left-hand side of each substitution is one of its symbols.
+                let mut cursor = node.walk();
+                for sub in node
+                    .named_children(&mut cursor)
+                    .filter(|c| c.kind() == "substitution")
+                {
+                    let mut sub_cursor = sub.walk();
+                    for rhs in sub
+                        .children(&mut sub_cursor)
+                        .skip_while(|c| c.kind() != "gets")
+                        .skip(1)
+                    {
+                        self.walk(rhs);
+                    }
+                }
+            }
+            "record_value" => {
+                // `r.field`: only the record expression is a reference.
+                if let Some(record) = node.named_child(0) {
+                    self.walk(record);
+                }
+            }
+            "prefixed_op" => {
+                // `M!Op(args)`: resolve the instance name and the arguments,
+                // but not `Op`, which lives in the instantiated module.
+                if let Some(component) = node
+                    .child_by_field_name("prefix")
+                    .and_then(|p| p.named_child(0))
+                    .and_then(|c| c.named_child(0))
+                    .filter(|c| c.kind() == "identifier_ref")
+                {
+                    self.walk(component);
+                }
+                if let Some(op) = node.child_by_field_name("op") {
+                    if op.kind() == "bound_op" {
+                        let mut cursor = op.walk();
+                        for arg in op.children_by_field_name("parameter", &mut cursor) {
+                            self.walk(arg);
+                        }
+                    }
+                }
+            }
+            "assume_prove" => {
+                self.push_scope(ScopeKind::AssumeProve, node);
+                let mut introduced = Vec::new();
+                collect_new_symbols(node, &mut introduced);
+                for sym in introduced {
+                    match sym.kind() {
+                        "operator_declaration" => {
+                            if let Some(name) = sym.child_by_field_name("name") {
+                                self.declare(name, SymbolKind::Parameter, sym, declared_arity(sym));
+                            }
+                        }
+                        _ => {
+                            self.declare(sym, SymbolKind::Parameter, node, 0);
+                        }
+                    }
+                }
+                self.walk_children(node);
+                self.pop_scope();
+            }
+            "theorem" => {
+                if let Some(statement) = node.child_by_field_name("statement") {
+                    self.walk(statement);
+                }
+            }
+            // Declarations introduce names but reference none; proofs,
+            // subexpression selectors and PlusCal comments are out of scope;
+            // broken regions are reported as syntax errors instead.
+            "extends"
+            | "variable_declaration"
+            | "constant_declaration"
+            | "recursive_declaration"
+            | "except_update_record_field"
+            | "subexpression"
+            | "use_or_hide"
+            | "terminal_proof"
+            | "non_terminal_proof"
+            | "block_comment"
+            | "pcal_algorithm"
+            | "ERROR" => {}
+            _ => self.walk_children(node),
+        }
+    }
+
+    fn walk_children(&mut self, node: Node) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.walk(child);
+        }
+    }
+
+    /// Binders built from `quantifier_bound`s: the bounding sets are
+    /// evaluated outside the new scope, everything else inside it.
+    fn walk_bounded(&mut self, kind: ScopeKind, node: Node) {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        let bounds: Vec<Node> = children
+            .iter()
+            .copied()
+            .filter(|c| c.kind() == "quantifier_bound")
+            .collect();
+        for bound in &bounds {
+            if let Some(set) = bound.child_by_field_name("set") {
+                self.walk(set);
+            }
+        }
+        self.push_scope(kind, node);
+        for bound in &bounds {
+            for ident in intro_names(*bound) {
+                self.declare(ident, SymbolKind::BoundVariable, *bound, 0);
+            }
+        }
+        let name_field = node.child_by_field_name("name");
+        for child in children {
+            if child.kind() != "quantifier_bound" && Some(child) != name_field {
+                self.walk(child);
+            }
+        }
+        self.pop_scope();
+    }
+
+    /// Declares the names a module or LET unit introduces in the current scope.
+    fn declare_unit(&mut self, unit: Node) {
+        let (unit, local) = if unit.kind() == "local_definition" {
+            match unit.named_child(0) {
+                Some(inner) => (inner, true),
+                None => return,
+            }
+        } else {
+            (unit, false)
+        };
+
+        match unit.kind() {
+            "variable_declaration" | "constant_declaration" | "recursive_declaration" => {
+                let kind = match unit.kind() {
+                    "variable_declaration" => SymbolKind::Variable,
+                    "constant_declaration" => SymbolKind::Constant,
+                    _ => SymbolKind::RecursiveDeclaration,
+                };
+                let mut cursor = unit.walk();
+                for child in unit.named_children(&mut cursor) {
+                    match child.kind() {
+                        "identifier" => {
+                            self.declare(child, kind, unit, 0);
+                        }
+                        "operator_declaration" => {
+                            if let Some(name) = child.child_by_field_name("name") {
+                                self.declare(name, kind, child, declared_arity(child));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "operator_definition"
+            | "function_definition"
+            | "module_definition"
+            | "theorem"
+            | "assumption" => {
+                let kind = match unit.kind() {
+                    "operator_definition" => SymbolKind::Operator,
+                    "function_definition" => SymbolKind::Function,
+                    "module_definition" => SymbolKind::ModuleInstance,
+                    "theorem" => SymbolKind::Theorem,
+                    _ => SymbolKind::Assumption,
+                };
+                let arity = match kind {
+                    SymbolKind::Operator | SymbolKind::ModuleInstance => parameters(unit).len(),
+                    _ => 0,
+                };
+                for name in unit_names(unit) {
+                    let id = self.declare(name, kind, unit, arity);
+                    self.table.symbols[id.0].local = local;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn declare_parameter(&mut self, param: Node, def: Node) {
+        match param.kind() {
+            "operator_declaration" => {
+                if let Some(name) = param.child_by_field_name("name") {
+                    self.declare(name, SymbolKind::Parameter, def, declared_arity(param));
+                }
+            }
+            _ => {
+                self.declare(param, SymbolKind::Parameter, def, 0);
+            }
+        }
+    }
+
+    fn declare(
+        &mut self,
+        name: Node,
+        kind: SymbolKind,
+        definition: Node,
+        arity: usize,
+    ) -> SymbolId {
+        let scope = *self.stack.last().expect("scope stack is never empty");
+        self.table.add_symbol(Symbol {
+            name: text(name, self.src).to_string(),
+            kind,
+            scope,
+            name_range: name.byte_range(),
+            definition_range: definition.byte_range(),
+            arity,
+            local: false,
+        })
+    }
+
+    fn reference(&mut self, node: Node) {
+        let scope = *self.stack.last().expect("scope stack is never empty");
+        let name = text(node, self.src).to_string();
+        let symbol = self.table.lookup(scope, &name);
+        self.table
+            .reference_index
+            .insert(node.start_byte(), self.table.references.len());
+        self.table.references.push(Reference {
+            name,
+            range: node.byte_range(),
+            scope,
+            symbol,
+        });
+    }
+
+    fn push_scope(&mut self, kind: ScopeKind, node: Node) -> ScopeId {
+        let id = ScopeId(self.table.scopes.len());
+        self.table.scopes.push(Scope {
+            kind,
+            parent: self.stack.last().copied(),
+            range: node.byte_range(),
+            symbols: Vec::new(),
+            bindings: HashMap::new(),
+        });
+        self.stack.push(id);
+        id
+    }
+
+    fn pop_scope(&mut self) {
+        self.stack.pop();
+    }
+}
+
+fn collect_new_symbols<'t>(node: Node<'t>, out: &mut Vec<Node<'t>>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "new" => {
+                let mut inner = child.walk();
+                for sym in child.named_children(&mut inner) {
+                    if matches!(sym.kind(), "identifier" | "operator_declaration") {
+                        out.push(sym);
+                    }
+                }
+            }
+            "inner_assume_prove" | "assume_prove" => collect_new_symbols(child, out),
+            _ => {}
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::{Context, Result, anyhow};
 use std::env;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
+use std::process::Command;
 
 pub struct ToolStatus {
     pub present: bool,
@@ -29,9 +31,72 @@ pub fn ensure_tool(name: &'static str) -> Result<()> {
     Err(anyhow!(status.hint))
 }
 
+/// The `tla2tools.jar` named by `TLA_TOOLS_JAR`, if set.
+pub fn tools_jar() -> Option<PathBuf> {
+    env::var_os("TLA_TOOLS_JAR").map(PathBuf::from)
+}
+
+/// A command that translates the PlusCal algorithm of the module file named
+/// by its last argument in place, as `java -cp tla2tools.jar pcal.trans`
+/// does.
+#[derive(Clone, Debug)]
+pub struct PcalTranslator {
+    pub program: PathBuf,
+    pub args: Vec<OsString>,
+}
+
+impl PcalTranslator {
+    /// The translator in `TLA_TOOLS_JAR`, when the jar exists and `java` is
+    /// on PATH.
+    pub fn from_env() -> Option<Self> {
+        let jar = tools_jar().filter(|jar| jar.is_file())?;
+        let java = which::which("java").ok()?;
+        Some(Self {
+            program: java,
+            args: vec![
+                "-cp".into(),
+                jar.into_os_string(),
+                "pcal.trans".into(),
+                "-nocfg".into(),
+            ],
+        })
+    }
+
+    /// Translates `src` in a scratch directory, saved as `file_name` so the
+    /// translator sees the right module name, and returns the result.
+    pub fn translate(&self, file_name: &OsStr, src: &str) -> Result<String> {
+        let dir = tempfile::tempdir().context("failed to create a scratch directory")?;
+        let path = dir.path().join(file_name);
+        fs::write(&path, src).with_context(|| format!("failed to write {}", path.display()))?;
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .arg(file_name)
+            .current_dir(dir.path())
+            .output()
+            .with_context(|| format!("failed to run {}", self.program.display()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let reason = stderr
+                .lines()
+                .chain(stdout.lines())
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .unwrap_or("no output")
+                .to_string();
+            return Err(anyhow!(
+                "{} exited with {}: {reason}",
+                self.program.display(),
+                output.status
+            ));
+        }
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))
+    }
+}
+
 pub fn write_tlc_wrapper(out_path: PathBuf, jar: Option<PathBuf>) -> Result<()> {
     let jar_path = jar
-        .or_else(|| env::var("TLA_TOOLS_JAR").ok().map(PathBuf::from))
+        .or_else(tools_jar)
         .ok_or_else(|| anyhow!("--jar <path> or TLA_TOOLS_JAR must be provided"))?;
 
     let jar_abs = fs::canonicalize(&jar_path)
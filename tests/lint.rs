@@ -1,29 +1,72 @@
 use std::path::PathBuf;
-use tla_cli::lint::types::{RuleCode, Severity};
-use tla_cli::lint::{collect_diagnostics, reporter};
+use tla_cli::lint::config::RuleSettings;
+use tla_cli::lint::naming::{Case, NamingConventions};
+use tla_cli::lint::types::{Diagnostic, Edit, Fix, RuleCode, Severity};
+use tla_cli::lint::{
+    LintOptions, apply_fixes, cfg, collect_diagnostics, collect_diagnostics_with, config, fix,
+    registry, reporter, rules,
+};
+use tla_cli::tooling::PcalTranslator;
 
 fn fixture(name: &str) -> PathBuf {
     PathBuf::from("fixtures").join(name)
 }
 
+/// Code, position and message of each diagnostic, sorted by position.
+/// With `code`, diagnostics of other rules are left out.
+fn found(diags: &[Diagnostic], code: Option<RuleCode>) -> Vec<(RuleCode, usize, usize, &str)> {
+    let mut found: Vec<_> = diags
+        .iter()
+        .filter(|d| code.is_none_or(|code| d.code == code))
+        .map(|d| (d.code, d.line, d.column, d.message.as_str()))
+        .collect();
+    found.sort_by_key(|(_, line, column, _)| (*line, *column));
+    found
+}
+
 #[test]
 fn ok_fixture_has_no_diagnostics() {
-    let diags = collect_diagnostics(vec![fixture("ok.tla")]).expect("lint run");
+    let diags = collect_diagnostics(vec![fixture("Ok.tla")]).expect("lint run");
     assert!(diags.is_empty());
 }
 
 #[test]
 fn unused_variable_warns() {
-    let diags = collect_diagnostics(vec![fixture("unused.tla")]).expect("lint run");
+    let diags = collect_diagnostics(vec![fixture("Unused.tla")]).expect("lint run");
     assert_eq!(diags.len(), 1);
     let d = &diags[0];
     assert_eq!(d.code, RuleCode::TLA001);
     assert_eq!(d.severity, Severity::Warning);
 }
 
+#[test]
+fn bound_variable_is_not_a_use_of_state_variable() {
+    let diags = collect_diagnostics(vec![fixture("shadowed.tla")]).expect("lint run");
+    let unused: Vec<_> = diags
+        .iter()
+        .filter(|d| d.code == RuleCode::TLA001)
+        .collect();
+    assert_eq!(unused.len(), 1, "{diags:?}");
+    assert!(unused[0].message.contains("`x`"));
+    // The CHOOSE variable that shadows `x` is reported on its own.
+    assert!(diags.iter().any(|d| d.code == RuleCode::TLA014));
+}
+
+#[test]
+fn unused_constant_has_its_own_code() {
+    let diags =
+        collect_diagnostics(vec![fixture("unused_constants/Params.tla")]).expect("lint run");
+    assert_eq!(diags.len(), 1, "{diags:?}");
+    let d = &diags[0];
+    assert_eq!(d.code, RuleCode::TLA005);
+    assert_eq!(d.severity, Severity::Warning);
+    assert_eq!((d.line, d.column), (3, 33));
+    assert_eq!(d.message, "Constant `Unused` is declared but never used");
+}
+
 #[test]
 fn missing_next_errors() {
-    let diags = collect_diagnostics(vec![fixture("missing_next.tla")]).expect("lint run");
+    let diags = collect_diagnostics(vec![fixture("MissingNext.tla")]).expect("lint run");
     assert!(
         diags
             .iter()
@@ -31,9 +74,157 @@ fn missing_next_errors() {
     );
 }
 
+#[test]
+fn syntax_error_reports_location_and_expected_token() {
+    let diags = collect_diagnostics(vec![fixture("syntax_error.tla")]).expect("lint run");
+    assert_eq!(diags.len(), 1, "{diags:?}");
+    let d = &diags[0];
+    assert_eq!(d.code, RuleCode::TLA000);
+    assert_eq!(d.severity, Severity::Error);
+    assert_eq!((d.line, d.column), (7, 3));
+    assert!(d.end_line.is_some() && d.end_column.is_some());
+    assert!(d.message.contains("expected `)` to close `(` at 5:14"));
+}
+
+#[test]
+fn one_missing_paren_is_one_syntax_error() {
+    let diags = collect_diagnostics(vec![fixture("unclosed_paren.tla")]).expect("lint run");
+    assert_eq!(diags.len(), 1, "{diags:?}");
+    let d = &diags[0];
+    assert_eq!(d.code, RuleCode::TLA000);
+    assert_eq!((d.line, d.column), (5, 38));
+    assert!(d.message.contains("expected `)` to close `(` at 5:24"));
+}
+
+#[test]
+fn syntax_errors_name_what_is_missing() {
+    let diags = collect_diagnostics(vec![
+        fixture("missing_terminator.tla"),
+        fixture("if_without_else.tla"),
+    ])
+    .expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA000,
+                4,
+                19,
+                "Syntax error: unexpected `1`; expected `ELSE` to complete `IF` at 4:9"
+            ),
+            (
+                RuleCode::TLA000,
+                6,
+                19,
+                "Syntax error: missing `====` at the end of the module"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn undefined_identifiers_skip_the_rest_of_a_broken_definition() {
+    let diags = collect_diagnostics(vec![fixture("broken_params.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (RuleCode::TLA000, 5, 7, "Syntax error: unexpected `(`"),
+            (RuleCode::TLA003, 7, 13, "Undefined identifier `Start`"),
+        ]
+    );
+}
+
+#[test]
+fn undefined_identifiers_skip_a_definition_swallowed_by_the_one_before() {
+    let diags = collect_diagnostics(vec![fixture("broken_next.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![(
+            RuleCode::TLA000,
+            3,
+            20,
+            "Syntax error: expected an expression after `=`, found the definition of `Next`"
+        )]
+    );
+}
+
+#[test]
+fn undefined_identifiers_error() {
+    let diags = collect_diagnostics(vec![fixture("undefined.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, Some(RuleCode::TLA003)),
+        vec![
+            (RuleCode::TLA003, 7, 35, "Undefined identifier `Deq`"),
+            (RuleCode::TLA003, 8, 49, "Undefined identifier `Max`"),
+        ]
+    );
+}
+
+#[test]
+fn names_must_be_defined_before_use() {
+    let diags = collect_diagnostics(vec![fixture("forward.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA003,
+                5,
+                13,
+                "`Start` is used before it is defined"
+            ),
+            (RuleCode::TLA003, 7, 23, "`b` is used before it is defined"),
+        ]
+    );
+    assert_eq!((diags[0].labels[0].line, diags[0].labels[0].column), (6, 1));
+    assert_eq!(
+        (diags[1].labels[0].line, diags[1].labels[0].column),
+        (8, 18)
+    );
+}
+
+#[test]
+fn unused_operators_warn_unless_used_by_cfg_or_other_modules() {
+    let diags =
+        collect_diagnostics(vec![fixture("unused_operators/Counter.tla")]).expect("lint run");
+    let unused: Vec<_> = diags
+        .iter()
+        .filter(|d| d.code == RuleCode::TLA004)
+        .map(|d| (d.line, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        unused,
+        vec![
+            (8, "Operator `Dead` is defined but never used"),
+            (10, "Operator `Countdown` is defined but never used"),
+        ]
+    );
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+}
+
+#[test]
+fn tlc_config_names_are_collected() {
+    let config = cfg::parse(
+        "\\* comment INVARIANT Ignored\n\
+         CONSTANTS\n  N = 3\n  Procs = {p1, p2}\n  F <- MCF\n  G <- [Other] H\n\
+         (* INIT Nope *)\n\
+         SPECIFICATION Spec\nINVARIANTS TypeOK Inv\nCHECK_DEADLOCK FALSE\n",
+    );
+    let mut ops: Vec<_> = config.operators.iter().map(String::as_str).collect();
+    ops.sort();
+    assert_eq!(ops, vec!["H", "Inv", "MCF", "Spec", "TypeOK"]);
+    let mut consts: Vec<_> = config.constants.iter().map(String::as_str).collect();
+    consts.sort();
+    assert_eq!(consts, vec!["F", "G", "N", "Procs"]);
+    let mut invariants: Vec<_> = config.invariants.iter().map(String::as_str).collect();
+    invariants.sort();
+    assert_eq!(invariants, vec!["Inv", "TypeOK"]);
+    let specs: Vec<_> = config.specifications.iter().map(String::as_str).collect();
+    assert_eq!(specs, vec!["Spec"]);
+}
+
 #[test]
 fn json_output_is_stable() {
-    let diags = collect_diagnostics(vec![fixture("unused.tla"), fixture("missing_next.tla")])
+    let diags = collect_diagnostics(vec![fixture("Unused.tla"), fixture("MissingNext.tla")])
         .expect("lint run");
     let json = reporter::to_json(&diags).expect("json");
     insta::assert_snapshot!("json_output", json);
@@ -43,7 +234,7 @@ fn json_output_is_stable() {
 fn ok_fixture_tree_shape() {
     use tla_cli::tla_parser::TlaParser;
     let mut parser = TlaParser::new().unwrap();
-    let src = std::fs::read_to_string(fixture("ok.tla")).unwrap();
+    let src = std::fs::read_to_string(fixture("Ok.tla")).unwrap();
     let tree = parser.parse(&src).expect("parse ok");
     let root = tree.root_node();
     assert!(!root.has_error());
@@ -79,3 +270,1208 @@ fn ok_fixture_tree_shape() {
         ]
     );
 }
+
+#[test]
+fn actions_must_assign_or_leave_unchanged_every_variable() {
+    let diags = collect_diagnostics(vec![fixture("unchanged.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA006,
+                12,
+                1,
+                "Action `Reset` neither assigns nor leaves UNCHANGED: `z`"
+            ),
+            (
+                RuleCode::TLA006,
+                15,
+                1,
+                "Action `Swap` neither assigns nor leaves UNCHANGED: `y`"
+            ),
+            (
+                RuleCode::TLA006,
+                25,
+                12,
+                "Disjunct of `Next` neither assigns nor leaves UNCHANGED: `y`, `z`"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn primes_are_rejected_outside_actions() {
+    let diags = collect_diagnostics(vec![fixture("primes/Primes.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Error));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA007,
+                8,
+                10,
+                "`x'` is primed inside `Init`, which must be a state predicate"
+            ),
+            (
+                RuleCode::TLA007,
+                13,
+                24,
+                "`y'` is primed inside invariant `TypeOK`, which must be a state predicate"
+            ),
+            (
+                RuleCode::TLA007,
+                17,
+                20,
+                "Cannot prime constant `N`; only state variables have a next-state value"
+            ),
+            (
+                RuleCode::TLA007,
+                18,
+                32,
+                "Cannot prime bound variable `n`; only state variables have a next-state value"
+            ),
+            (RuleCode::TLA007, 20, 10, "`x''` is primed twice"),
+        ]
+    );
+}
+
+#[test]
+fn level_errors_are_reported() {
+    let diags = collect_diagnostics(vec![fixture("levels/Levels.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, Some(RuleCode::TLA008)),
+        vec![
+            (
+                RuleCode::TLA008,
+                7,
+                1,
+                "Invariant `Safe` has action level; INVARIANT requires a state predicate, check it as PROPERTY [][Safe]_vars instead"
+            ),
+            (
+                RuleCode::TLA008,
+                8,
+                1,
+                "Invariant `Live` has temporal level; INVARIANT requires a state predicate, check it as a PROPERTY instead"
+            ),
+            (
+                RuleCode::TLA008,
+                9,
+                1,
+                "`Init` has temporal level; the initial predicate must be a state predicate"
+            ),
+            (
+                RuleCode::TLA008,
+                10,
+                19,
+                "`ENABLED` inside `Next`; TLC cannot evaluate it in the next-state relation"
+            ),
+            (
+                RuleCode::TLA008,
+                11,
+                12,
+                "Temporal formula inside `Next`, which must be an action"
+            ),
+            (
+                RuleCode::TLA008,
+                12,
+                22,
+                "UNCHANGED expects an operand of at most state level, found action level"
+            ),
+            (
+                RuleCode::TLA008,
+                13,
+                20,
+                "`[A]_v` expects an operand of at most action level, found temporal level"
+            ),
+            (
+                RuleCode::TLA008,
+                13,
+                59,
+                "Subscript of `[A]_v` must be a state function, found action level"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn assumptions_and_constant_operator_arguments_must_be_constant() {
+    let diags = collect_diagnostics(vec![fixture("levels/ConstantLevels.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA008,
+                6,
+                8,
+                "ASSUME expects a formula of at most constant level, found state level"
+            ),
+            (
+                RuleCode::TLA008,
+                10,
+                27,
+                "CONSTANT operator `Leq` expects arguments of at most constant level, found state level"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn function_definitions_have_the_level_of_their_body() {
+    let diags =
+        collect_diagnostics(vec![fixture("function_levels/FunctionLevels.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA007,
+                5,
+                18,
+                "`x'` is primed inside `Init`, which must be a state predicate"
+            ),
+            (
+                RuleCode::TLA007,
+                5,
+                18,
+                "`x'` is primed inside invariant `Inv`, which must be a state predicate"
+            ),
+            (
+                RuleCode::TLA008,
+                8,
+                8,
+                "ASSUME expects a formula of at most constant level, found state level"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn spec_must_reference_init_next_and_cover_all_variables() {
+    let diags = collect_diagnostics(vec![fixture("spec_shape/SpecShape.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA009,
+                11,
+                16,
+                "Fairness subscript `<<x, y, z>>` differs from the `[][Next]_vars` subscript"
+            ),
+            (
+                RuleCode::TLA009,
+                13,
+                26,
+                "Subscript `vars` omits `z`; steps that change only those variables count as stuttering"
+            ),
+            (
+                RuleCode::TLA009,
+                15,
+                1,
+                "`LiveSpec` does not reference `Init`"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn duplicate_definitions_error() {
+    let diags = collect_diagnostics(vec![fixture("duplicates.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Error));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA010,
+                4,
+                17,
+                "Variable `x` is declared twice; first declared at 4:11"
+            ),
+            (
+                RuleCode::TLA010,
+                5,
+                10,
+                "`N` is declared both as VARIABLE and CONSTANT; first declared at 3:10"
+            ),
+            (RuleCode::TLA010, 9, 9, "`a` is already defined at 9:6"),
+            (
+                RuleCode::TLA010,
+                11,
+                1,
+                "`Len` is already defined by the standard module Sequences"
+            ),
+            (RuleCode::TLA010, 15, 1, "`Next` is already defined at 13:1"),
+        ]
+    );
+}
+
+#[test]
+fn operator_calls_must_match_arity() {
+    let diags = collect_diagnostics(vec![fixture("arity.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA011,
+                12,
+                17,
+                "`Append` expects 2 arguments, got 1"
+            ),
+            (RuleCode::TLA011, 13, 30, "`Add` expects 2 arguments, got 3"),
+            (
+                RuleCode::TLA011,
+                14,
+                19,
+                "`Cardinality` expects 1 argument, got 2"
+            ),
+            (
+                RuleCode::TLA011,
+                15,
+                40,
+                "Parameter `F` of `Apply` expects an operator taking 2 arguments, got `Inc` taking 1 argument"
+            ),
+            (
+                RuleCode::TLA011,
+                15,
+                55,
+                "Parameter `F` of `Apply` expects an operator taking 2 arguments, got `LAMBDA a: a` taking 1 argument"
+            ),
+            (
+                RuleCode::TLA011,
+                16,
+                45,
+                "`Inc` expects 1 argument but is used without any"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn recursion_requires_matching_recursive_declaration() {
+    let diags = collect_diagnostics(vec![fixture("recursive.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA012,
+                3,
+                11,
+                "RECURSIVE `Sum` declares 1 argument but the definition takes 2"
+            ),
+            (
+                RuleCode::TLA012,
+                3,
+                19,
+                "RECURSIVE `Ghost` has no matching definition"
+            ),
+            (
+                RuleCode::TLA012,
+                5,
+                1,
+                "`Fact` calls itself but is not declared RECURSIVE"
+            ),
+            (
+                RuleCode::TLA012,
+                6,
+                1,
+                "`Even` is mutually recursive with `Odd` but is not declared RECURSIVE"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn module_names_must_match_file_and_be_unique() {
+    let diags = collect_diagnostics(vec![fixture("module_names/Wrong.tla")]).expect("lint run");
+    assert_eq!(
+        found(&diags, Some(RuleCode::TLA013)),
+        vec![
+            (
+                RuleCode::TLA013,
+                1,
+                13,
+                "Module `Right` must be in a file named `Right.tla`"
+            ),
+            (
+                RuleCode::TLA013,
+                8,
+                13,
+                "Nested module `Inner` conflicts with the module declared at 4:13"
+            ),
+            (
+                RuleCode::TLA013,
+                12,
+                13,
+                "Nested module `Right` conflicts with the module declared at 1:13"
+            ),
+            (
+                RuleCode::TLA013,
+                20,
+                13,
+                "File contains more than one top-level module; move `Extra` to its own file"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn shadowing_reports_both_sites() {
+    let diags = collect_diagnostics(vec![fixture("shadowing.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA014,
+                10,
+                6,
+                "Parameter `N` shadows constant `N` at 3:10"
+            ),
+            (
+                RuleCode::TLA014,
+                12,
+                13,
+                "LET definition `Max` shadows definition `Max` at 6:1"
+            ),
+            (
+                RuleCode::TLA014,
+                14,
+                15,
+                "Bound variable `x` shadows variable `x` at 4:10"
+            ),
+            (
+                RuleCode::TLA014,
+                15,
+                29,
+                "Bound variable `x` shadows variable `x` at 4:10"
+            ),
+            (
+                RuleCode::TLA014,
+                16,
+                18,
+                "Bound variable `Init` shadows definition `Init` at 8:1"
+            ),
+        ]
+    );
+}
+
+fn naming_enabled() -> RuleSettings {
+    RuleSettings {
+        enabled: [
+            RuleCode::TLA015,
+            RuleCode::TLA016,
+            RuleCode::TLA017,
+            RuleCode::TLA018,
+        ]
+        .into(),
+        ..RuleSettings::default()
+    }
+}
+
+#[test]
+fn naming_conventions_are_opt_in() {
+    let path = fixture("naming/Naming.tla");
+    let default = collect_diagnostics(vec![path.clone()]).expect("lint run");
+    assert!(default.is_empty(), "{default:?}");
+
+    let options = LintOptions {
+        rules: naming_enabled(),
+        ..LintOptions::default()
+    };
+    let diags = collect_diagnostics_with(vec![path], &options).expect("lint run");
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA017,
+                3,
+                22,
+                "Constant `timeout` should be UPPER_CASE"
+            ),
+            (
+                RuleCode::TLA016,
+                4,
+                20,
+                "Variable `Msg_Queue` should be camelCase"
+            ),
+            (
+                RuleCode::TLA018,
+                10,
+                1,
+                "Invariant `Safe` should start with `TypeOK` or `Inv`"
+            ),
+            (
+                RuleCode::TLA015,
+                14,
+                1,
+                "Operator `send_msg` should be PascalCase"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn naming_conventions_skip_the_pluscal_translation() {
+    // The translator picks `Init`, `pc`, `vars` and the label actions; none
+    // of them is the author's to rename.
+    let options = LintOptions {
+        naming: NamingConventions {
+            operators: Case::Snake,
+            variables: Case::Upper,
+            ..NamingConventions::default()
+        },
+        rules: naming_enabled(),
+        ..LintOptions::default()
+    };
+    let diags =
+        collect_diagnostics_with(vec![fixture("pluscal/Counter.tla")], &options).expect("lint run");
+    assert!(diags.is_empty(), "{diags:?}");
+}
+
+#[test]
+fn case_needs_other_arm_and_distinct_guards() {
+    let diags = collect_diagnostics(vec![fixture("case_arms.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA019,
+                7,
+                10,
+                "CASE has no OTHER arm; TLC fails at runtime on states where no guard holds"
+            ),
+            (
+                RuleCode::TLA020,
+                12,
+                14,
+                "CASE guard `x = 0` duplicates the guard at 10:14; TLC never selects this arm"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn state_space_hazards() {
+    let diags = collect_diagnostics(vec![fixture("hazards.tla")]).expect("lint run");
+    assert!(
+        diags
+            .iter()
+            .filter(|d| d.code == RuleCode::TLA021)
+            .all(|d| d.severity == Severity::Warning)
+    );
+    assert_eq!(
+        found(&diags, Some(RuleCode::TLA021)),
+        vec![
+            (
+                RuleCode::TLA021,
+                6,
+                8,
+                "`SUBSET SUBSET` has 2^(2^n) elements; TLC enumerates it even for a 3-element set"
+            ),
+            (
+                RuleCode::TLA021,
+                9,
+                18,
+                "`Init` draws from infinite set `Nat`; TLC cannot enumerate the initial states, use a finite range such as 0..N"
+            ),
+            (
+                RuleCode::TLA021,
+                10,
+                18,
+                "`Init` draws from `[SUBSET S -> S]`, which is doubly exponential in size; TLC generates one initial state per element"
+            ),
+            (
+                RuleCode::TLA021,
+                12,
+                22,
+                "CHOOSE ranges over infinite set `Nat`; TLC cannot enumerate it, use a finite range such as 0..N"
+            ),
+            (
+                RuleCode::TLA021,
+                18,
+                21,
+                "Quantifier ranges over infinite set `Nat`; TLC cannot enumerate it, use a finite range such as 0..N"
+            ),
+            (
+                RuleCode::TLA021,
+                19,
+                21,
+                "Quantifier ranges over infinite set `Msgs`; TLC cannot enumerate it, bound the sequence length, e.g. UNION {[1..n -> S] : n \\in 0..N}"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn state_space_hazards_only_in_what_tlc_evaluates() {
+    let diags = collect_diagnostics(vec![fixture("hazards_model")]).expect("lint run");
+    let infinite = "Quantifier ranges over infinite set `Nat`; TLC cannot enumerate it, use a finite range such as 0..N";
+    // The INVARIANT and the CONSTRAINT of the .cfg, not the LEMMA, the
+    // THEOREM or its proof.
+    assert_eq!(
+        found(&diags, Some(RuleCode::TLA021)),
+        vec![
+            (RuleCode::TLA021, 8, 21, infinite),
+            (RuleCode::TLA021, 9, 18, infinite),
+        ]
+    );
+}
+
+#[test]
+fn unused_extends_and_instances() {
+    let diags =
+        collect_diagnostics(vec![fixture("unused_imports/UnusedImports.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA022,
+                2,
+                30,
+                "Module `FiniteSets` is extended but none of its definitions are used"
+            ),
+            (
+                RuleCode::TLA022,
+                2,
+                42,
+                "Module `TLC` is extended but none of its definitions are used"
+            ),
+            (
+                RuleCode::TLA022,
+                2,
+                56,
+                "Module `Extra` is extended but none of its definitions are used"
+            ),
+            (
+                RuleCode::TLA023,
+                6,
+                1,
+                "Instance `E` is defined but never used"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn pluscal_translation_staleness() {
+    let diags = collect_diagnostics(vec![fixture("pluscal")]).expect("lint run");
+    let mut found: Vec<(String, usize, usize, &str)> = diags
+        .iter()
+        .map(|d| {
+            assert_eq!(d.code, RuleCode::TLA024);
+            assert_eq!(d.severity, Severity::Error);
+            let file = PathBuf::from(&d.file);
+            let name = file.file_name().unwrap().to_string_lossy().into_owned();
+            (name, d.line, d.column, d.message.as_str())
+        })
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "Stale.tla".to_string(),
+                15,
+                1,
+                "PlusCal translation is out of date: variable `y` is not declared, label `Loop` has no action, label `Bump` has no action and 1 more; re-run the PlusCal translator"
+            ),
+            (
+                "Untranslated.tla".to_string(),
+                4,
+                16,
+                "PlusCal algorithm `Untranslated` has no translation; run the PlusCal translator"
+            ),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn pluscal_translation_is_compared_with_the_translator() {
+    let translator = |args: &[&str]| LintOptions {
+        translator: Some(PcalTranslator {
+            program: PathBuf::from("sh"),
+            args: args.iter().map(|arg| arg.into()).collect(),
+        }),
+        ..LintOptions::default()
+    };
+    let fake = std::fs::canonicalize(fixture("pcal_trans/fake-pcal.sh")).unwrap();
+    let fake = fake.to_str().unwrap();
+
+    // The statement edit keeps every label and variable, so only the
+    // translator's output shows it.
+    let output = std::fs::canonicalize(fixture("pcal_trans/Edited.tla")).unwrap();
+    let options = translator(&[fake, output.to_str().unwrap()]);
+    let diags =
+        collect_diagnostics_with(vec![fixture("pluscal/Edited.tla")], &options).expect("lint run");
+    assert_eq!(diags.len(), 1, "{diags:?}");
+    let stale = &diags[0];
+    assert_eq!(
+        (stale.code, stale.line, stale.column),
+        (RuleCode::TLA024, 13, 1)
+    );
+    assert_eq!(
+        stale.notes,
+        vec![
+            "the algorithm changed since it was translated (chksum(pcal) was \"1f0c6d2e\", now \"5b92e0a4\")",
+            "line 24 is `THEN /\\ x' = x + 1` but the translator writes `THEN /\\ x' = x + 2`",
+        ]
+    );
+    assert_eq!(stale.help, None);
+
+    // An up-to-date translation is what the translator writes again.
+    let output = std::fs::canonicalize(fixture("pluscal/Counter.tla")).unwrap();
+    let options = translator(&[fake, output.to_str().unwrap()]);
+    let diags =
+        collect_diagnostics_with(vec![fixture("pluscal/Counter.tla")], &options).expect("lint run");
+    assert!(diags.is_empty(), "{diags:?}");
+
+    // A translator that fails leaves the structural comparison, and says so.
+    let options = translator(&["-c", "echo 'unrecoverable error' >&2; exit 1"]);
+    let diags =
+        collect_diagnostics_with(vec![fixture("pluscal/Stale.tla")], &options).expect("lint run");
+    let help = diags[0].help.as_deref().unwrap_or_default();
+    assert!(
+        help.starts_with(
+            "the PlusCal translator failed (sh exited with exit status: 1: unrecoverable error)"
+        ),
+        "{help}"
+    );
+    assert!(
+        help.contains("edits inside statements are not detected"),
+        "{help}"
+    );
+}
+
+#[test]
+fn pluscal_rules() {
+    let diags = collect_diagnostics(vec![fixture("pluscal_rules/Rules.tla")]).expect("lint run");
+    let mut found: Vec<(RuleCode, usize, usize, &str)> = diags
+        .iter()
+        .filter(|d| d.code != RuleCode::TLA024)
+        .map(|d| (d.code, d.line, d.column, d.message.as_str()))
+        .collect();
+    found.sort_by_key(|(_, line, col, _)| (*line, *col));
+    assert_eq!(
+        found,
+        vec![
+            (
+                RuleCode::TLA025,
+                16,
+                3,
+                "Statement after `return` must be labeled"
+            ),
+            (
+                RuleCode::TLA026,
+                24,
+                3,
+                "`x` is assigned twice in one step (first at 23:3); add a label before the second assignment"
+            ),
+            (RuleCode::TLA025, 25, 3, "`while` must be labeled"),
+            (
+                RuleCode::TLA025,
+                30,
+                3,
+                "Statement after a `call` must be labeled"
+            ),
+            (
+                RuleCode::TLA028,
+                33,
+                3,
+                "`await` follows an assignment in the same step; the whole step, assignment included, waits for the condition. Label the `await` to make it a step of its own"
+            ),
+            (
+                RuleCode::TLA027,
+                34,
+                3,
+                "Assignment to undeclared variable `z`"
+            ),
+            (
+                RuleCode::TLA026,
+                35,
+                3,
+                "`y` is assigned twice in one step (first at 32:3); add a label before the second assignment"
+            ),
+            (
+                RuleCode::TLA025,
+                40,
+                3,
+                "First statement of process `Single` must be labeled"
+            ),
+            (
+                RuleCode::TLA025,
+                45,
+                3,
+                "Statement after an `if` that contains a label, `call`, `return` or `goto` must be labeled"
+            ),
+        ]
+    );
+    let await_warning = diags.iter().find(|d| d.code == RuleCode::TLA028).unwrap();
+    assert_eq!(await_warning.severity, Severity::Warning);
+}
+
+#[test]
+fn rules_are_enabled_as_the_registry_says() {
+    let defaults = RuleSettings::default();
+    for rule in registry::RULES {
+        assert_eq!(
+            defaults.is_enabled(rule.code),
+            rule.enabled_by_default,
+            "{}",
+            rule.code
+        );
+    }
+
+    let options = config::parse("[lint]\nenable = [\"TLA015\"]\ndisable = [\"TLA001\"]\n")
+        .expect("valid config");
+    assert!(options.rules.is_enabled(RuleCode::TLA015));
+    assert!(!options.rules.is_enabled(RuleCode::TLA016));
+    assert!(!options.rules.is_enabled(RuleCode::TLA001));
+    assert!(options.rules.is_enabled(RuleCode::TLA004));
+}
+
+#[test]
+fn tla_toml_selects_rules_and_severities() {
+    let path = fixture("config/scratch/Scratch.tla");
+    let diags = collect_diagnostics(vec![path.clone()]).expect("lint run");
+    let mut found: Vec<(RuleCode, Severity, usize, usize)> = diags
+        .iter()
+        .map(|d| (d.code, d.severity, d.line, d.column))
+        .collect();
+    found.sort_by_key(|(_, _, line, col)| (*line, *col));
+    assert_eq!(
+        found,
+        vec![
+            (RuleCode::TLA016, Severity::Warning, 3, 18),
+            (RuleCode::TLA016, Severity::Warning, 3, 29),
+            (RuleCode::TLA014, Severity::Error, 6, 15),
+            (RuleCode::TLA004, Severity::Warning, 9, 1),
+        ]
+    );
+
+    // Explicit options bypass the file.
+    let defaults = collect_diagnostics_with(vec![path], &LintOptions::default()).expect("lint run");
+    assert!(defaults.iter().any(|d| d.code == RuleCode::TLA001));
+    assert!(defaults.iter().all(|d| d.code != RuleCode::TLA016));
+}
+
+#[test]
+fn invalid_tla_toml_is_reported() {
+    let err = collect_diagnostics(vec![fixture("config_invalid")]).unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains("config_invalid/tla.toml"), "{message}");
+    assert!(message.contains("line 2"), "{message}");
+    assert!(
+        message.contains(
+            "unknown rule code `tla004`; codes run from TLA000 to TLA029, did you mean `TLA004`?"
+        ),
+        "{message}"
+    );
+
+    let cases = [
+        ("[fmt]\n", "line 1", "unknown field `fmt`"),
+        ("[lint]\nignore = []\n", "line 2", "unknown field `ignore`"),
+        (
+            "[lint.severity]\nTLA001 = \"fatal\"\n",
+            "line 2",
+            "unknown variant `fatal`, expected `warning` or `error`",
+        ),
+        (
+            "[lint.naming]\nvariables = \"kebab-case\"\n",
+            "line 2",
+            "unknown case `kebab-case`",
+        ),
+        ("disable = []\n", "line 1", "unknown field `disable`"),
+        ("[lint]\ndisable = [\"TLA001\"\n", "line 2", "invalid array"),
+        ("[lint]\ndisable = []\n[lint]\n", "line 3", "duplicate key"),
+    ];
+    for (src, line, expected) in cases {
+        let err = config::parse(src).unwrap_err().to_string();
+        assert!(
+            err.contains(line) && err.contains(expected),
+            "{src:?}: {err}"
+        );
+    }
+    let err = config::parse("[lint]\nenable = [\"TLA016\"]\ndisable = [\"TLA016\"]\n").unwrap_err();
+    assert_eq!(err.to_string(), "TLA016 is both enabled and disabled");
+}
+
+#[test]
+fn tla_toml_accepts_any_toml_spelling() {
+    let expected = config::parse(
+        "[lint]\ndisable = [\"TLA001\"]\n[lint.severity]\nTLA014 = \"error\"\n\
+         [lint.naming]\nvariables = \"snake_case\"\n",
+    )
+    .expect("valid config");
+    let spellings = [
+        "[lint]\ndisable = ['TLA001']\nseverity = { TLA014 = 'error' }\n\
+         naming = { variables = '''snake_case''' }\n",
+        "lint.disable = [\"TLA001\"]\nlint.severity.TLA014 = \"error\"\n\
+         [ lint . naming ]\nvariables = \"snake_case\"\n",
+        "[lint]\ndisable = [\n  \"TLA001\", # trailing comma\n]\n\n\
+         [\"lint\".severity]\n\"TLA014\" = \"error\"\n[lint.naming]\nvariables = \"snake_case\"\n",
+    ];
+    for src in spellings {
+        let options = config::parse(src).expect(src);
+        assert_eq!(options.rules, expected.rules, "{src}");
+        assert_eq!(options.naming, expected.naming, "{src}");
+    }
+}
+
+#[test]
+fn inline_suppressions() {
+    let diags = collect_diagnostics(vec![fixture("suppress.tla")]).expect("lint run");
+    assert!(diags.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA005,
+                11,
+                11,
+                "Constant `Loud` is declared but never used"
+            ),
+            (
+                RuleCode::TLA029,
+                13,
+                1,
+                "Unused suppression: no TLA004 diagnostics to silence"
+            ),
+            (
+                RuleCode::TLA029,
+                16,
+                1,
+                "Unused suppression: no diagnostics to silence"
+            ),
+            (
+                RuleCode::TLA029,
+                17,
+                1,
+                "`enable TLA004` without a preceding `disable`"
+            ),
+            (
+                RuleCode::TLA029,
+                18,
+                1,
+                "Unknown suppression `silence`; expected `disable-next-line`, `disable`, `enable` or `disable-file`"
+            ),
+            (
+                RuleCode::TLA029,
+                19,
+                1,
+                "Unknown rule code `TLA999` in suppression"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn suppressions_for_skipped_rules_are_not_unused() {
+    let diags = collect_diagnostics(vec![fixture("suppress_broken.tla")]).expect("lint run");
+    // TLA004 and the blanket suppression wait for the file to parse; TLA013
+    // runs on a broken tree too.
+    assert_eq!(
+        found(&diags, None),
+        vec![
+            (
+                RuleCode::TLA000,
+                11,
+                14,
+                "Syntax error: unexpected `0`; expected `)` to close `(` at 11:13"
+            ),
+            (
+                RuleCode::TLA029,
+                13,
+                1,
+                "Unused suppression: no TLA013 diagnostics to silence"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fixes_are_offered_in_json() {
+    let diags = collect_diagnostics(vec![fixture("fix/Fixable.tla")]).expect("lint run");
+    let unused = diags
+        .iter()
+        .find(|d| d.code == RuleCode::TLA001)
+        .expect("unused variable");
+    let fix = unused.fix.as_ref().expect("fix");
+    assert_eq!(
+        fix.description,
+        "Remove `unused` from the variable declaration"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&reporter::to_json(&diags).expect("json")).expect("valid json");
+    let fixes = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|d| d.get("fix").is_some())
+        .count();
+    assert_eq!(fixes, diags.iter().filter(|d| d.fix.is_some()).count());
+    assert!(fixes >= 5, "{json}");
+}
+
+#[test]
+fn fix_rewrites_files_and_relints() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for name in ["Fixable.tla", "Skeleton.tla"] {
+        std::fs::copy(fixture("fix").join(name), dir.path().join(name)).expect("copy");
+    }
+
+    let applied = apply_fixes(vec![dir.path().to_path_buf()]).expect("fix run");
+    assert_eq!(applied, 8);
+
+    let fixable = std::fs::read_to_string(dir.path().join("Fixable.tla")).unwrap();
+    for expected in [
+        "VARIABLES x, y, z\n",
+        "       /\\ y' = y\n       /\\ UNCHANGED z\n",
+        "Swap == /\\ x' = y /\\ UNCHANGED <<y, z>>\n",
+        "Reset == /\\ x' = 0 /\\ UNCHANGED <<z, y>>\n",
+        "          [] OTHER -> FALSE\n",
+        "\\/ y' = 1 /\\ z' = 2 /\\ UNCHANGED x\n",
+    ] {
+        assert!(
+            fixable.contains(expected),
+            "missing {expected:?} in\n{fixable}"
+        );
+    }
+    let skeleton = std::fs::read_to_string(dir.path().join("Skeleton.tla")).unwrap();
+    assert!(
+        skeleton.contains(
+            "VARIABLES a, b\n\nInit ==\n    /\\ a = 0\n    /\\ b = 0\n\nNext ==\n    UNCHANGED <<a, b>>\n"
+        ),
+        "{skeleton}"
+    );
+
+    let diags = collect_diagnostics(vec![dir.path().to_path_buf()]).expect("lint run");
+    assert!(diags.iter().all(|d| d.fix.is_none()), "{diags:?}");
+    assert!(
+        diags.iter().all(|d| !matches!(
+            d.code,
+            RuleCode::TLA001 | RuleCode::TLA002 | RuleCode::TLA006
+        )),
+        "{diags:?}"
+    );
+}
+
+#[test]
+fn fix_keeps_variables_used_by_extending_modules() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for name in ["Base.tla", "MC.tla"] {
+        std::fs::copy(fixture("fix_external").join(name), dir.path().join(name)).expect("copy");
+    }
+
+    let diags = collect_diagnostics(vec![dir.path().to_path_buf()]).expect("lint run");
+    assert!(
+        !diags.iter().any(|d| d.code == RuleCode::TLA001),
+        "{diags:?}"
+    );
+
+    apply_fixes(vec![dir.path().to_path_buf()]).expect("fix run");
+    let base = std::fs::read_to_string(dir.path().join("Base.tla")).unwrap();
+    assert!(base.contains("VARIABLES x, y\n"), "{base}");
+    let diags = collect_diagnostics(vec![dir.path().to_path_buf()]).expect("lint run");
+    assert!(
+        !diags.iter().any(|d| d.code == RuleCode::TLA003),
+        "{diags:?}"
+    );
+}
+
+#[test]
+fn fix_is_undone_when_it_adds_errors_to_another_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for name in ["Base.tla", "MC.tla", "tla.toml"] {
+        std::fs::copy(fixture("fix_blocked").join(name), dir.path().join(name)).expect("copy");
+    }
+
+    // Adding `Init` to Base would make the parameter of MC's `Start` shadow
+    // it, which tla.toml turns into an error.
+    let applied = apply_fixes(vec![dir.path().to_path_buf()]).expect("fix run");
+    assert_eq!(applied, 0);
+    let base = std::fs::read_to_string(dir.path().join("Base.tla")).unwrap();
+    assert_eq!(
+        base,
+        std::fs::read_to_string(fixture("fix_blocked/Base.tla")).unwrap()
+    );
+}
+
+#[test]
+fn overlapping_fixes_are_applied_one_at_a_time() {
+    let diag = |start, end, replacement: &str| Diagnostic {
+        file: "t.tla".to_string(),
+        line: 1,
+        column: 1,
+        end_line: None,
+        end_column: None,
+        start_byte: None,
+        end_byte: None,
+        severity: Severity::Warning,
+        code: RuleCode::TLA001,
+        message: String::new(),
+        labels: Vec::new(),
+        notes: Vec::new(),
+        help: None,
+        fix: Some(Fix {
+            description: String::new(),
+            edits: vec![Edit {
+                start,
+                end,
+                replacement: replacement.to_string(),
+            }],
+        }),
+    };
+    let src = "VARIABLES a, b, c";
+    let diags = [diag(10, 13, ""), diag(11, 16, ""), diag(17, 17, ", d")];
+    assert_eq!(
+        fix::apply(src, &diags),
+        ("VARIABLES b, c, d".to_string(), 2)
+    );
+}
+
+#[test]
+fn diagnostics_carry_ranges_labels_and_notes() {
+    let diags = collect_diagnostics(vec![
+        fixture("MissingNext.tla"),
+        fixture("duplicates.tla"),
+        fixture("pluscal/Stale.tla"),
+    ])
+    .expect("lint run");
+
+    // TLA002 points at the module name and labels the declaration.
+    let missing = diags
+        .iter()
+        .find(|d| d.code == RuleCode::TLA002)
+        .expect("missing Next");
+    assert_eq!(
+        (
+            missing.line,
+            missing.column,
+            missing.end_line,
+            missing.end_column
+        ),
+        (1, 13, Some(1), Some(24))
+    );
+    assert_eq!((missing.start_byte, missing.end_byte), (Some(12), Some(23)));
+    assert_eq!(missing.labels.len(), 1);
+    assert_eq!(
+        (missing.labels[0].line, missing.labels[0].message.as_str()),
+        (2, "variables declared here")
+    );
+    assert!(missing.help.is_some());
+
+    let duplicate = diags
+        .iter()
+        .find(|d| d.code == RuleCode::TLA010 && d.message.starts_with("Variable `x`"))
+        .expect("duplicate variable");
+    let label = &duplicate.labels[0];
+    assert_eq!(
+        (label.line, label.column, label.message.as_str()),
+        (4, 11, "first declared here")
+    );
+
+    let stale = diags
+        .iter()
+        .find(|d| d.code == RuleCode::TLA024)
+        .expect("stale translation");
+    assert_eq!(stale.notes.len(), 4, "{:?}", stale.notes);
+    assert!(
+        stale
+            .help
+            .as_deref()
+            .is_some_and(|help| help.contains("edits inside statements are not detected"))
+    );
+    assert!(
+        stale
+            .notes
+            .contains(&"action `Inc` has no label in the algorithm".to_string())
+    );
+
+    // Every diagnostic of a parsed file has a byte range.
+    assert!(
+        diags
+            .iter()
+            .all(|d| d.start_byte.is_some() && d.end_byte.is_some())
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&reporter::to_json(&diags).expect("json")).expect("valid json");
+    let missing = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["code"] == "TLA002")
+        .expect("missing Next in json");
+    assert_eq!(missing["labels"][0]["start_byte"], 29);
+    assert!(missing["help"].is_string());
+}
+
+#[test]
+fn registry_describes_every_rule_once() {
+    let codes: Vec<RuleCode> = registry::RULES.iter().map(|r| r.code).collect();
+    assert_eq!(codes, RuleCode::ALL);
+    for rule in registry::RULES {
+        assert_eq!(registry::rule(rule.code).name, rule.name);
+        assert!(!rule.summary.ends_with('.'), "{:?}", rule.code);
+        assert!(rule.explanation.ends_with('.'), "{:?}", rule.code);
+    }
+
+    // Every rule but TLA029, which suppression reports itself, belongs to
+    // exactly one check.
+    for code in RuleCode::ALL {
+        let owners = rules::CHECKS
+            .iter()
+            .filter(|check| check.codes.contains(code))
+            .count();
+        let expected = usize::from(*code != RuleCode::TLA029);
+        assert_eq!(owners, expected, "{code:?}");
+    }
+}
+
+#[test]
+fn diagnostics_use_registry_severities() {
+    let files = [
+        "arity.tla",
+        "case_arms.tla",
+        "duplicates.tla",
+        "hazards.tla",
+        "MissingInit.tla",
+        "recursive.tla",
+        "shadowing.tla",
+        "suppress.tla",
+        "syntax_error.tla",
+        "unchanged.tla",
+        "undefined.tla",
+        "Unused.tla",
+    ];
+    let diags = collect_diagnostics(files.iter().map(|f| fixture(f)).collect()).expect("lint run");
+    assert!(!diags.is_empty());
+    for d in &diags {
+        assert_eq!(d.severity, registry::rule(d.code).severity, "{d:?}");
+    }
+}
+
+#[test]
+fn rules_can_be_listed_and_explained() {
+    let table = reporter::rules_table(registry::RULES);
+    assert_eq!(table.lines().count(), RuleCode::ALL.len() + 1);
+    assert!(table.contains("TLA006  unchanged-variables"));
+    assert!(table.contains("(opt-in)"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&reporter::rules_json(registry::RULES).expect("json"))
+            .expect("valid json");
+    let first = &json.as_array().unwrap()[0];
+    assert_eq!(first["code"], "TLA000");
+    assert_eq!(first["category"], "syntax");
+    assert_eq!(first["severity"], "error");
+
+    let code = registry::parse_code("tla015").unwrap_err();
+    assert!(code.contains("did you mean `TLA015`"), "{code}");
+    let text = reporter::explanation(registry::rule(registry::parse_code("TLA015").unwrap()));
+    assert!(text.starts_with("TLA015 operator-naming\n"));
+    assert!(text.contains("Off unless enabled in tla.toml."));
+    assert!(text.contains("\n    type_ok == x \\in Nat"));
+}
@@ -0,0 +1,111 @@
+use tla_cli::semantic::{ScopeKind, SymbolKind, SymbolTable};
+use tla_cli::tla_parser::TlaParser;
+
+const SRC: &str = r#"---- MODULE Sem ----
+EXTENDS Naturals
+CONSTANT N
+VARIABLE x
+RECURSIVE Sum(_)
+Sum(n) == IF n = 0 THEN 0 ELSE n + Sum(n - 1)
+Init == x = LET k == N IN k
+Next == \E x \in 1..N : x' = {y \in 1..x : y > 0}
+===="#;
+
+fn table() -> SymbolTable {
+    let mut parser = TlaParser::new().unwrap();
+    let tree = parser.parse(SRC).expect("parse");
+    SymbolTable::build(&tree, SRC, ["Nat"])
+}
+
+fn offset(needle: &str) -> usize {
+    SRC.find(needle).expect("needle in source")
+}
+
+#[test]
+fn builds_nested_scopes() {
+    let table = table();
+    let kinds: Vec<ScopeKind> = table.scopes().iter().map(|s| s.kind).collect();
+    assert_eq!(kinds[0], ScopeKind::Imports);
+    assert_eq!(kinds[1], ScopeKind::Module);
+    for kind in [
+        ScopeKind::OperatorParameters,
+        ScopeKind::Let,
+        ScopeKind::Quantifier,
+        ScopeKind::SetComprehension,
+    ] {
+        assert!(kinds.contains(&kind), "missing {kind:?} in {kinds:?}");
+    }
+}
+
+#[test]
+fn bound_variable_shadows_state_variable() {
+    let table = table();
+    // `x'` inside `\E x \in 1..N` refers to the bound variable.
+    let id = table.symbol_at(offset("x' =")).expect("resolved");
+    assert_eq!(table.symbol(id).kind, SymbolKind::BoundVariable);
+
+    let state = table
+        .module_symbols()
+        .find(|(_, s)| s.name == "x")
+        .map(|(id, _)| id)
+        .unwrap();
+    // Only the use in Init resolves to the VARIABLE.
+    assert_eq!(table.references_to(state).count(), 1);
+}
+
+#[test]
+fn recursive_reference_resolves_to_definition() {
+    let table = table();
+    let id = table.symbol_at(offset("Sum(n - 1)")).expect("resolved");
+    let sym = table.symbol(id);
+    assert_eq!(sym.kind, SymbolKind::Operator);
+    assert_eq!(sym.arity, 1);
+    assert_eq!(sym.name_range.start, offset("Sum(n) =="));
+}
+
+#[test]
+fn let_and_imports_resolve() {
+    let table = table();
+    let k = table.symbol_at(offset("k\n")).expect("resolved");
+    assert_eq!(table.symbol(k).kind, SymbolKind::Operator);
+    assert_eq!(table.scope(table.symbol(k).scope).kind, ScopeKind::Let);
+    assert_eq!(table.unresolved().count(), 0);
+}
+
+#[test]
+fn forward_references_do_not_resolve() {
+    let src = r#"---- MODULE Fwd ----
+Init == Start
+Start == LET a == b
+             b == 1
+         IN a
+RECURSIVE F(_)
+G(n) == F(n)
+F(n) == IF n = 0 THEN 0 ELSE G(n - 1)
+===="#;
+    let mut parser = TlaParser::new().unwrap();
+    let tree = parser.parse(src).expect("parse");
+    let table = SymbolTable::build(&tree, src, Vec::<String>::new());
+
+    let unresolved: Vec<(&str, usize)> = table
+        .unresolved()
+        .map(|r| (r.name.as_str(), r.range.start))
+        .collect();
+    assert_eq!(
+        unresolved,
+        vec![
+            ("Start", src.find("Start\n").unwrap()),
+            ("b", src.find("b\n").unwrap()),
+        ]
+    );
+
+    // A RECURSIVE declaration lets `G` call `F` ahead of its definition.
+    let id = table
+        .symbol_at(src.find("F(n)\n").unwrap())
+        .expect("resolved");
+    assert_eq!(table.symbol(id).kind, SymbolKind::Operator);
+    assert_eq!(
+        table.symbol(id).name_range.start,
+        src.find("F(n) ==").unwrap()
+    );
+}
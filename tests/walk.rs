@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+use tla_cli::lint::collect_diagnostics;
+use tla_cli::lint::types::RuleCode;
+
+// A test binary of its own: it changes the working directory of the whole
+// process.
+#[test]
+fn linting_dot_walks_the_current_directory() {
+    std::env::set_current_dir("fixtures/unused_constants").expect("fixture directory");
+    let diags = collect_diagnostics(vec![PathBuf::from(".")]).expect("lint run");
+    assert!(
+        diags
+            .iter()
+            .any(|d| d.code == RuleCode::TLA005 && d.file.ends_with("Params.tla")),
+        "{diags:?}"
+    );
+}